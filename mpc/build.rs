@@ -3,13 +3,12 @@
  * SPDX-License-Identifier: GPL-2.0-only
  */
 
-use lalrpop;
 use std::env;
 use std::path;
 
 fn main() {
   // Define MPC_STD_DIR if wasn't previously defined
-  if let Err(..) = env::var("MPC_STD_DIR") {
+  if env::var("MPC_STD_DIR").is_err() {
     let manifest_dir =
       env::var("CARGO_MANIFEST_DIR").unwrap();
     let std_path =
@@ -0,0 +1,49 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+use crate::util::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Write a Makefile rule making targets depend on every source file read
+pub fn write(path: &Path, targets: &[PathBuf], deps: &[&Path]) -> MRes<()> {
+  let mut rule = String::new();
+  for target in targets.iter() {
+    rule.push_str(&escape(target));
+    rule.push(' ');
+  }
+  rule.pop();
+  rule.push(':');
+  for dep in deps.iter() {
+    rule.push_str(" \\\n  ");
+    rule.push_str(&escape(dep));
+  }
+  rule.push('\n');
+
+  // Empty rules for the dependencies, so deleting one doesn't break the build
+  for dep in deps.iter() {
+    rule.push('\n');
+    rule.push_str(&escape(dep));
+    rule.push_str(":\n");
+  }
+
+  fs::write(path, rule)
+    .map_err(|error| format!("{}: {}", path.display(), error))?;
+  Ok(())
+}
+
+/// Escape characters that are special to make
+fn escape(path: &Path) -> String {
+  let mut escaped = String::new();
+  for ch in path.to_string_lossy().chars() {
+    match ch {
+      ' ' | '#' => escaped.push('\\'),
+      '$' => escaped.push('$'),
+      _ => ()
+    }
+    escaped.push(ch);
+  }
+  escaped
+}
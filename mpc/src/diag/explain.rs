@@ -0,0 +1,58 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+/// Long-form explanations of error codes
+
+static EXPLANATIONS: &[(&str, &str)] = &[
+  ("E0001", include_str!("explain/E0001.md")),
+  ("E0002", include_str!("explain/E0002.md")),
+  ("E0003", include_str!("explain/E0003.md")),
+  ("E0004", include_str!("explain/E0004.md")),
+  ("E0005", include_str!("explain/E0005.md")),
+  ("E0006", include_str!("explain/E0006.md")),
+  ("E0007", include_str!("explain/E0007.md")),
+  ("E0008", include_str!("explain/E0008.md")),
+  ("E0009", include_str!("explain/E0009.md")),
+  ("E0010", include_str!("explain/E0010.md")),
+  ("E0011", include_str!("explain/E0011.md")),
+  ("E0101", include_str!("explain/E0101.md")),
+  ("E0102", include_str!("explain/E0102.md")),
+  ("E0103", include_str!("explain/E0103.md")),
+  ("E0104", include_str!("explain/E0104.md")),
+  ("E0105", include_str!("explain/E0105.md")),
+  ("E0106", include_str!("explain/E0106.md")),
+  ("E0107", include_str!("explain/E0107.md")),
+  ("E0201", include_str!("explain/E0201.md")),
+  ("E0202", include_str!("explain/E0202.md")),
+  ("E0203", include_str!("explain/E0203.md")),
+  ("E0204", include_str!("explain/E0204.md")),
+  ("E0205", include_str!("explain/E0205.md")),
+  ("E0206", include_str!("explain/E0206.md")),
+  ("E0207", include_str!("explain/E0207.md")),
+  ("E0208", include_str!("explain/E0208.md")),
+  ("E0209", include_str!("explain/E0209.md")),
+  ("E0210", include_str!("explain/E0210.md")),
+  ("E0211", include_str!("explain/E0211.md")),
+  ("E0212", include_str!("explain/E0212.md")),
+  ("E0213", include_str!("explain/E0213.md")),
+  ("E0214", include_str!("explain/E0214.md")),
+  ("E0215", include_str!("explain/E0215.md")),
+  ("E0216", include_str!("explain/E0216.md")),
+  ("E0217", include_str!("explain/E0217.md")),
+  ("E0218", include_str!("explain/E0218.md")),
+  ("E0219", include_str!("explain/E0219.md")),
+  ("E0220", include_str!("explain/E0220.md")),
+  ("E0221", include_str!("explain/E0221.md")),
+  ("E0222", include_str!("explain/E0222.md")),
+  ("E0301", include_str!("explain/E0301.md"))
+];
+
+/// Find the explanation of an error code, e.g. `E0203`
+pub fn explain(code: &str) -> Option<&'static str> {
+  EXPLANATIONS
+    .iter()
+    .find(|(other, _)| other.eq_ignore_ascii_case(code))
+    .map(|(_, explanation)| *explanation)
+}
@@ -0,0 +1,176 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+use super::*;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+struct Style {
+  color: bool
+}
+
+impl Style {
+  fn paint(&self, code: &'static str, text: &str) -> String {
+    if self.color {
+      format!("{}{}{}", code, text, RESET)
+    } else {
+      text.to_string()
+    }
+  }
+}
+
+/// Render a diagnostic with source snippets and underlined labels
+pub(super) fn render(diagnostic: &Diagnostic, w: &mut dyn Write, color: bool) -> io::Result<()> {
+  let style = Style { color };
+  let severity_color = match diagnostic.severity {
+    Severity::Error => RED,
+    Severity::Warning => YELLOW,
+    Severity::Note | Severity::Help => BOLD
+  };
+
+  // Header
+  let severity = match diagnostic.code {
+    Some(code) => format!("{}[{}]", diagnostic.severity, code),
+    None => diagnostic.severity.to_string()
+  };
+  writeln!(w, "{}{}",
+           style.paint(severity_color, &severity),
+           style.paint(BOLD, &format!(": {}", diagnostic.message)))?;
+
+  // Put primary labels first, then group the rest by file and line
+  let mut labels: Vec<&Label> = diagnostic.labels.iter().collect();
+  labels.sort_by_key(|label| !label.primary);
+  let primary_path = labels.first().map(|label| label.path.clone());
+  labels.sort_by(|a, b| {
+    let a_key = (Some(&a.path) != primary_path.as_ref(), &a.path, a.start.line);
+    let b_key = (Some(&b.path) != primary_path.as_ref(), &b.path, b.start.line);
+    a_key.cmp(&b_key)
+  });
+
+  let gutter_width = labels
+    .iter()
+    .map(|label| label.start.line.to_string().len())
+    .max()
+    .unwrap_or(0);
+  let gutter = style.paint(BLUE, &format!("{} |", " ".repeat(gutter_width)));
+
+  let mut index = 0;
+  while index < labels.len() {
+    // Collect labels on the same line
+    let first = labels[index];
+    let mut end = index + 1;
+    while end < labels.len()
+        && labels[end].path == first.path
+        && labels[end].start.line == first.start.line {
+      end += 1;
+    }
+    let group = &labels[index..end];
+
+    // Location of the snippet, the first one uses an arrow
+    if index == 0 || labels[index - 1].path != first.path {
+      let location = labels[index..]
+        .iter()
+        .take_while(|label| label.path == first.path)
+        .find(|label| label.primary)
+        .unwrap_or(&first);
+      let arrow = if index == 0 { "-->" } else { ":::" };
+      writeln!(w, "{}{} {}:{}:{}",
+               " ".repeat(gutter_width),
+               style.paint(BLUE, arrow),
+               location.path.display(),
+               location.start.line,
+               location.start.column)?;
+      writeln!(w, "{}", gutter)?;
+    } else if labels[index - 1].start.line + 1 != first.start.line {
+      writeln!(w, "{}", style.paint(BLUE, "..."))?;
+    }
+
+    render_line(w, &style, severity_color, gutter_width, &gutter, group)?;
+    index = end;
+  }
+
+  // Notes and help messages
+  for (severity, message) in diagnostic.children.iter() {
+    writeln!(w, "{} {} {} {}",
+             " ".repeat(gutter_width),
+             style.paint(BLUE, "="),
+             style.paint(BOLD, &format!("{}:", severity)),
+             message)?;
+  }
+
+  Ok(())
+}
+
+/// Render a source line and the labels pointing into it
+fn render_line(w: &mut dyn Write,
+               style: &Style,
+               severity_color: &'static str,
+               gutter_width: usize,
+               gutter: &str,
+               group: &[&Label]) -> io::Result<()> {
+  let first = group[0];
+  let line_number = format!("{:>width$} |", first.start.line, width = gutter_width);
+  writeln!(w, "{} {}", style.paint(BLUE, &line_number), first.line)?;
+
+  // Find the columns covered by each label
+  let line_len = first.line.chars().count();
+  let mut ranges: Vec<(usize, usize, &Label)> = group
+    .iter()
+    .map(|label| {
+      let lo = label.start.column - 1;
+      let hi = if label.end.line == label.start.line {
+        label.end.column - 1
+      } else {
+        line_len
+      };
+      (lo, hi.max(lo + 1), *label)
+    })
+    .collect();
+  ranges.sort_by_key(|(lo, ..)| *lo);
+
+  // Underline every label on one row
+  let mut underline = String::new();
+  let mut column = 0;
+  for (lo, hi, label) in ranges.iter() {
+    if *lo < column {
+      continue
+    }
+    underline.push_str(&indent(&first.line, column, *lo));
+    let (marker, code) = if label.primary { ("^", severity_color) } else { ("-", BLUE) };
+    underline.push_str(&style.paint(code, &marker.repeat(hi - lo)));
+    column = *hi;
+  }
+
+  // The rightmost message goes next to the underline, the rest below
+  let mut messages = ranges.iter().rev().peekable();
+  match messages.peek() {
+    Some((.., label)) if !label.message.is_empty() => {
+      let code = if label.primary { severity_color } else { BLUE };
+      writeln!(w, "{} {} {}", gutter, underline, style.paint(code, &label.message))?;
+      messages.next();
+    }
+    _ => writeln!(w, "{} {}", gutter, underline)?
+  }
+  for (lo, _, label) in messages.filter(|(.., label)| !label.message.is_empty()) {
+    let code = if label.primary { severity_color } else { BLUE };
+    writeln!(w, "{} {}{}", gutter, indent(&first.line, 0, *lo), style.paint(code, &label.message))?;
+  }
+
+  Ok(())
+}
+
+/// Whitespace lining up with columns lo..hi of a source line, tabs are preserved
+fn indent(line: &str, lo: usize, hi: usize) -> String {
+  line.chars()
+    .chain(std::iter::repeat(' '))
+    .skip(lo)
+    .take(hi - lo)
+    .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+    .collect()
+}
@@ -0,0 +1,85 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+//
+// JSON diagnostics
+//
+// Each diagnostic is written as a single line JSON object:
+//
+// {
+//   "severity": "error",
+//   "code": "E0201",
+//   "message": "Cannot unify types Int32 and Bool",
+//   "file": "hello.m",
+//   "span": { "start": { "line": 2, "column": 18 }, "end": { "line": 2, "column": 22 } },
+//   "labels": [ { "primary": false, "file": ..., "span": ..., "message": ... } ],
+//   "children": [ { "severity": "note" or "help", "message": "..." } ]
+// }
+//
+// The top level "file" and "span" refer to the primary label, and are null
+// for diagnostics that are not tied to a source location.
+//
+
+use super::*;
+
+pub(super) fn render(diagnostic: &Diagnostic, w: &mut dyn Write) -> io::Result<()> {
+  write!(w, "{{\"severity\":{},\"code\":", string(&diagnostic.severity.to_string()))?;
+  match diagnostic.code {
+    Some(code) => write!(w, "{}", string(code))?,
+    None => write!(w, "null")?
+  }
+  write!(w, ",\"message\":{}", string(&diagnostic.message))?;
+
+  match diagnostic.primary_label() {
+    Some(label) => write!(w, ",\"file\":{},\"span\":{}", path(label), span(label))?,
+    None => write!(w, ",\"file\":null,\"span\":null")?
+  }
+
+  write!(w, ",\"labels\":[")?;
+  for (index, label) in diagnostic.labels.iter().enumerate() {
+    if index > 0 {
+      write!(w, ",")?;
+    }
+    write!(w, "{{\"primary\":{},\"file\":{},\"span\":{},\"message\":{}}}",
+           label.primary, path(label), span(label), string(&label.message))?;
+  }
+
+  write!(w, "],\"children\":[")?;
+  for (index, (severity, message)) in diagnostic.children.iter().enumerate() {
+    if index > 0 {
+      write!(w, ",")?;
+    }
+    write!(w, "{{\"severity\":{},\"message\":{}}}", string(&severity.to_string()), string(message))?;
+  }
+  writeln!(w, "]}}")
+}
+
+fn path(label: &Label) -> String {
+  string(&label.path.to_string_lossy())
+}
+
+fn span(label: &Label) -> String {
+  format!("{{\"start\":{{\"line\":{},\"column\":{}}},\"end\":{{\"line\":{},\"column\":{}}}}}",
+          label.start.line, label.start.column, label.end.line, label.end.column)
+}
+
+/// Quote and escape a JSON string
+fn string(s: &str) -> String {
+  let mut result = String::with_capacity(s.len() + 2);
+  result.push('"');
+  for ch in s.chars() {
+    match ch {
+      '"' => result.push_str("\\\""),
+      '\\' => result.push_str("\\\\"),
+      '\n' => result.push_str("\\n"),
+      '\r' => result.push_str("\\r"),
+      '\t' => result.push_str("\\t"),
+      ch if (ch as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", ch as u32)),
+      ch => result.push(ch)
+    }
+  }
+  result.push('"');
+  result
+}
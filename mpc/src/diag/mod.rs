@@ -0,0 +1,361 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+//
+// Diagnostics
+//
+// Errors produced by the various passes carry spans, which are meaningless
+// without the source map they refer to. Before leaving the compiler, they are
+// converted to diagnostics, which have all the source locations resolved,
+// and thus can be rendered by the driver.
+//
+
+use crate::lint::{LintLevel, LintOptions, Warning};
+use crate::parse;
+use crate::resolve::ResolveError;
+use crate::sema::{Bound, CannotUnifyError, InvalidConstantExpressionError, Mismatch, TypeError};
+use crate::source::{SourceMap, Span};
+use crate::util::ErrorList;
+use std::{error, fmt};
+use std::fmt::Formatter;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+mod explain;
+mod human;
+mod json;
+
+pub use crate::source::Position;
+pub use explain::explain;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+  Error,
+  Warning,
+  Note,
+  Help
+}
+
+impl fmt::Display for Severity {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      Severity::Error => write!(f, "error"),
+      Severity::Warning => write!(f, "warning"),
+      Severity::Note => write!(f, "note"),
+      Severity::Help => write!(f, "help")
+    }
+  }
+}
+
+/// Source range a diagnostic points at
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Label {
+  /// Primary labels point at the cause, secondary ones provide context
+  pub primary: bool,
+  pub path: PathBuf,
+  pub start: Position,
+  pub end: Position,
+  /// Text of the line the label starts on
+  pub line: String,
+  pub message: String
+}
+
+/// Compiler message with all source locations resolved
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diagnostic {
+  pub severity: Severity,
+  /// Error code identifying the kind of diagnostic
+  pub code: Option<&'static str>,
+  pub message: String,
+  pub labels: Vec<Label>,
+  /// Notes and help messages attached to the diagnostic
+  pub children: Vec<(Severity, String)>
+}
+
+impl Diagnostic {
+  pub fn error(message: impl Into<String>) -> Diagnostic {
+    Diagnostic {
+      severity: Severity::Error,
+      code: None,
+      message: message.into(),
+      labels: Vec::new(),
+      children: Vec::new()
+    }
+  }
+
+  pub fn warning(message: impl Into<String>) -> Diagnostic {
+    Diagnostic { severity: Severity::Warning, ..Diagnostic::error(message) }
+  }
+
+  pub(crate) fn code(mut self, code: &'static str) -> Diagnostic {
+    self.code = Some(code);
+    self
+  }
+
+  pub(crate) fn primary(self, sources: &SourceMap, span: Span, message: impl Into<String>) -> Diagnostic {
+    self.label(sources, true, span, message.into())
+  }
+
+  pub(crate) fn secondary(self, sources: &SourceMap, span: Span, message: impl Into<String>) -> Diagnostic {
+    self.label(sources, false, span, message.into())
+  }
+
+  pub(crate) fn note(mut self, note: impl Into<String>) -> Diagnostic {
+    self.children.push((Severity::Note, note.into()));
+    self
+  }
+
+  pub(crate) fn help(mut self, help: impl Into<String>) -> Diagnostic {
+    self.children.push((Severity::Help, help.into()));
+    self
+  }
+
+  fn label(mut self, sources: &SourceMap, primary: bool, span: Span, message: String) -> Diagnostic {
+    let file = sources.file(span.file);
+    let start = file.position(span.lo);
+    self.labels.push(Label {
+      primary,
+      path: file.path.clone(),
+      start,
+      end: file.position(span.hi),
+      line: file.line(start.line).to_string(),
+      message
+    });
+    self
+  }
+
+  /// Primary label, if the diagnostic has one
+  pub fn primary_label(&self) -> Option<&Label> {
+    self.labels.iter().find(|label| label.primary)
+  }
+
+  /// Render the diagnostic in a human readable form
+  pub fn render(&self, w: &mut dyn Write, color: bool) -> io::Result<()> {
+    human::render(self, w, color)
+  }
+
+  /// Render the diagnostic as a single line JSON object
+  pub fn render_json(&self, w: &mut dyn Write) -> io::Result<()> {
+    json::render(self, w)
+  }
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    let mut buffer = Vec::new();
+    self.render(&mut buffer, false).map_err(|_| fmt::Error)?;
+    write!(f, "{}", String::from_utf8_lossy(&buffer).trim_end())
+  }
+}
+
+impl error::Error for Diagnostic {}
+
+/// When to use ANSI colours for diagnostics
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorChoice {
+  Auto,
+  Always,
+  Never
+}
+
+impl ColorChoice {
+  pub const ALL: [&'static str; 3] = [ "auto", "always", "never" ];
+
+  /// Decide if diagnostics written to stderr should be coloured
+  pub fn for_stderr(&self) -> bool {
+    match self {
+      ColorChoice::Auto => io::stderr().is_terminal(),
+      ColorChoice::Always => true,
+      ColorChoice::Never => false
+    }
+  }
+}
+
+impl FromStr for ColorChoice {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "auto" => Ok(ColorChoice::Auto),
+      "always" => Ok(ColorChoice::Always),
+      "never" => Ok(ColorChoice::Never),
+      _ => Err(format!("Unknown color choice {}", s))
+    }
+  }
+}
+
+/// Format of diagnostics printed by the driver
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ErrorFormat {
+  Human,
+  Json
+}
+
+impl ErrorFormat {
+  pub const ALL: [&'static str; 2] = [ "human", "json" ];
+}
+
+impl FromStr for ErrorFormat {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "human" => Ok(ErrorFormat::Human),
+      "json" => Ok(ErrorFormat::Json),
+      _ => Err(format!("Unknown error format {}", s))
+    }
+  }
+}
+
+/// Downcast an error, looking through the extra box added by `Err(Box::new(..))?`
+fn downcast<'a, T: error::Error + 'static>(error: &'a (dyn error::Error + Send + Sync + 'static)) -> Option<&'a T> {
+  error.downcast_ref::<T>()
+    .or_else(|| error.downcast_ref::<Box<T>>().map(|error| &**error))
+}
+
+/// Convert lints triggered by the program to diagnostics, at the levels requested
+pub(crate) fn from_warnings(sources: &SourceMap, warnings: &[Warning], lints: &LintOptions) -> Vec<Diagnostic> {
+  let mut diagnostics = Vec::new();
+  for warning in warnings.iter() {
+    let diagnostic = match lints.level(warning.lint) {
+      LintLevel::Allow => continue,
+      LintLevel::Warn => Diagnostic::warning(&*warning.message),
+      LintLevel::Deny => Diagnostic::error(&*warning.message)
+        .note(format!("lint `{}` is denied", warning.lint))
+    };
+    let diagnostic = diagnostic
+      .code(warning.lint.name())
+      .primary(sources, warning.span, "");
+    diagnostics.push(match warning.label {
+      Some((span, message)) => diagnostic.secondary(sources, span, message),
+      None => diagnostic
+    });
+  }
+  diagnostics
+}
+
+/// Convert an error returned by any of the passes to diagnostics
+pub(crate) fn from_error(sources: &SourceMap, error: Box<dyn error::Error + Send + Sync>) -> Vec<Diagnostic> {
+  let mut diagnostics = Vec::new();
+  collect(sources, error, &mut diagnostics);
+  diagnostics
+}
+
+fn collect(sources: &SourceMap, error: Box<dyn error::Error + Send + Sync>, diagnostics: &mut Vec<Diagnostic>) {
+  match error.downcast::<ErrorList>() {
+    Ok(errors) => {
+      for error in errors.0.into_iter() {
+        collect(sources, error, diagnostics);
+      }
+    }
+    Err(error) => {
+      // The same error can be found more than once, e.g. in every instance of a generic function
+      let diagnostic = convert(sources, error);
+      if !diagnostics.contains(&diagnostic) {
+        diagnostics.push(diagnostic);
+      }
+    }
+  }
+}
+
+fn convert(sources: &SourceMap, error: Box<dyn error::Error + Send + Sync>) -> Diagnostic {
+  let message = error.to_string();
+  let diagnostic = Diagnostic::error(&*message);
+
+  if let Some(error) = downcast::<parse::Error>(&*error) {
+    let diagnostic = diagnostic.code(error.code());
+    match error {
+      parse::Error::UnexpectedToken(span, expected) |
+      parse::Error::UnexpectedEndOfFile(span, expected) if !expected.is_empty() => {
+        diagnostic
+          .primary(sources, *span, "")
+          .note(format!("expected one of {}", expected.join(", ")))
+      }
+      parse::Error::UnknownModule(span, _, tried) => {
+        tried
+          .iter()
+          .fold(diagnostic.primary(sources, *span, ""),
+                |diagnostic, path| diagnostic.note(format!("tried {}", path.display())))
+      }
+      parse::Error::Redefinition(span, name, previous) => {
+        diagnostic
+          .primary(sources, *span, "")
+          .secondary(sources, *previous, format!("previous definition of {} here", name))
+      }
+      _ => match error.span() {
+        Some(span) => diagnostic.primary(sources, span, ""),
+        None => diagnostic
+      }
+    }
+  } else if let Some(error) = downcast::<ResolveError>(&*error) {
+    let diagnostic = diagnostic
+      .code(error.code())
+      .primary(sources, error.span(), "");
+    match error {
+      ResolveError::UnresolvedPath(_, _, Some(suggestion)) |
+      ResolveError::UnresolvedImport(_, _, Some(suggestion)) => {
+        diagnostic.help(format!("did you mean `{}`?", suggestion))
+      }
+      ResolveError::PrivateDefinition(_, name, def_span) => {
+        diagnostic
+          .secondary(sources, *def_span, format!("{} is defined here", name))
+          .help(format!("mark {} as `pub` to use it from other modules", name))
+      }
+      ResolveError::GenericExport(..) => {
+        diagnostic.help("export a non-generic function calling the instance instead")
+      }
+      _ => diagnostic
+    }
+  } else if let Some(error) = downcast::<TypeError>(&*error) {
+    let diagnostic = diagnostic
+      .code(error.code())
+      .primary(sources, error.span(), "");
+    let diagnostic = match error {
+      TypeError::WrongTypeArgCount(_, def_span) => diagnostic.secondary(sources, *def_span, "defined here"),
+      _ => diagnostic
+    };
+    match error.suggestion() {
+      Some(suggestion) => diagnostic.help(format!("did you mean `{}`?", suggestion)),
+      None => diagnostic
+    }
+  } else if let Some(error) = downcast::<CannotUnifyError>(&*error) {
+    let diagnostic = diagnostic
+      .code(error.code())
+      .primary(sources, error.span, "");
+    let diagnostic = match error.annotation {
+      Some(annotation) => diagnostic.secondary(sources, annotation, "expected because of this annotation"),
+      None => diagnostic
+    };
+    match &*error.mismatch {
+      Mismatch::TypeDoesNotHaveBound(ty, bound) => {
+        diagnostic.note(format!("{:?} is not {}", ty, describe_bound(bound)))
+      }
+      _ => diagnostic
+    }
+  } else if let Some(error) = downcast::<InvalidConstantExpressionError>(&*error) {
+    diagnostic
+      .code(error.code())
+      .primary(sources, error.0, "")
+      .note("this expression has to be evaluated at compile time")
+  } else {
+    diagnostic
+  }
+}
+
+fn describe_bound(bound: &Bound) -> String {
+  match bound {
+    Bound::Is(ty) => format!("{:?}", ty),
+    Bound::Any => "any type".to_string(),
+    Bound::Eq => "a type that can be compared for equality".to_string(),
+    Bound::Num => "a numeric type".to_string(),
+    Bound::Int => "an integer type".to_string(),
+    Bound::Flt => "a floating point type".to_string()
+  }
+}
@@ -0,0 +1,108 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+use crate::util::*;
+use std::collections::HashSet;
+use std::fmt::{self, Formatter};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Intermediate representations that can be dumped
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DumpKind {
+  Ast,
+  Resolved,
+  Typed,
+  TVars,
+  Llvm
+}
+
+impl DumpKind {
+  pub const ALL: [&'static str; 5] = [ "ast", "resolved", "typed", "tvars", "llvm" ];
+}
+
+impl fmt::Display for DumpKind {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    let name = match self {
+      DumpKind::Ast => "ast",
+      DumpKind::Resolved => "resolved",
+      DumpKind::Typed => "typed",
+      DumpKind::TVars => "tvars",
+      DumpKind::Llvm => "llvm"
+    };
+    write!(f, "{}", name)
+  }
+}
+
+impl FromStr for DumpKind {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "ast" => Ok(DumpKind::Ast),
+      "resolved" => Ok(DumpKind::Resolved),
+      "typed" => Ok(DumpKind::Typed),
+      "tvars" => Ok(DumpKind::TVars),
+      "llvm" => Ok(DumpKind::Llvm),
+      _ => Err(format!("Unknown dump kind {}", s))
+    }
+  }
+}
+
+/// What to dump, and where
+
+#[derive(Clone, Debug, Default)]
+pub struct DumpOptions {
+  /// Representations to dump
+  pub kinds: HashSet<DumpKind>,
+  /// File to write dumps to, stderr is used if not specified
+  pub path: Option<PathBuf>
+}
+
+/// Dump writer for a single compilation
+
+pub(crate) struct Dumper {
+  kinds: HashSet<DumpKind>,
+  path: Option<PathBuf>,
+  // Only created once something is dumped
+  file: Option<File>
+}
+
+impl Dumper {
+  pub fn new(options: &DumpOptions) -> Dumper {
+    Dumper { kinds: options.kinds.clone(), path: options.path.clone(), file: None }
+  }
+
+  pub fn enabled(&self, kind: DumpKind) -> bool {
+    self.kinds.contains(&kind)
+  }
+
+  /// Dump a representation, if it was requested
+  pub fn dump<F>(&mut self, kind: DumpKind, f: F) -> MRes<()>
+    where F: FnOnce(&mut dyn Write) -> io::Result<()>
+  {
+    if !self.enabled(kind) {
+      return Ok(())
+    }
+    if let (None, Some(path)) = (&self.file, &self.path) {
+      self.file = Some(File::create(path)?);
+    }
+    match &mut self.file {
+      Some(file) => {
+        writeln!(file, "--- {} ---", kind)?;
+        f(file)?;
+      }
+      None => {
+        let mut stderr = io::stderr().lock();
+        writeln!(stderr, "--- {} ---", kind)?;
+        f(&mut stderr)?;
+      }
+    }
+    Ok(())
+  }
+}
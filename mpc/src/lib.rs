@@ -4,27 +4,165 @@
  */
 
 #![feature(hash_set_entry)]
-#![feature(hash_raw_entry)]
+// NOTE: doc comments are followed by a blank line before the item throughout
+#![allow(clippy::empty_line_after_doc_comments)]
 
 mod parse;
 mod resolve;
 mod sema;
 mod lower;
+mod link;
+mod dump;
+mod source;
+mod diag;
+mod lint;
+mod dep;
+mod mangle;
+mod sanitize;
+mod session;
 pub mod util;
 
 use crate::util::*;
-use std::path::Path;
+use crate::dump::Dumper;
+use crate::lint::Warning;
+use crate::source::SourceMap;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub use link::LinkOptions;
+pub use dump::{DumpKind, DumpOptions};
+pub use mpc_llvm::{CodeModel, OptLevel, RelocModel, TargetOptions};
+pub use diag::{explain, ColorChoice, Diagnostic, ErrorFormat, Label, Position, Severity};
+pub use lint::{Lint, LintLevel, LintOptions};
+pub use source::{FileSystem, MemorySources, SourceProvider};
+pub use mangle::{demangle, demangle_text};
+pub use lower::Check;
+pub use sanitize::Sanitizer;
+pub use session::{Analyzed, Artifact, ArtifactKind, Parsed, Resolved, Session};
 
 /// Choice of output artifact
 
 pub enum CompileTo {
   LLVMIr,
+  LLVMBitcode,
   Assembly,
-  Object
+  Object,
+  Executable(LinkOptions)
+}
+
+/// Options controlling code generation
+
+#[derive(Clone)]
+pub struct CompileOptions {
+  /// Target machine to generate code for
+  pub target: TargetOptions,
+  /// Optimization level
+  pub opt_level: OptLevel,
+  /// Generate DWARF debug info
+  pub debug_info: bool,
+  /// Runtime checks to generate
+  pub checks: HashSet<Check>,
+  /// Sanitizers to instrument the program with, their runtimes are linked into executables
+  pub sanitizers: HashSet<Sanitizer>,
+  /// Directories to search for imported modules, in order
+  pub include_dirs: Vec<PathBuf>,
+  /// Standard library directory, the one the compiler was built with is used if not specified
+  pub std_dir: Option<PathBuf>,
+  /// Intermediate representations to dump
+  pub dump: DumpOptions,
+  /// Lint levels
+  pub lints: LintOptions,
+  /// File to write the dependencies of the outputs to, in Make syntax
+  pub dep_file: Option<PathBuf>
+}
+
+impl Default for CompileOptions {
+  fn default() -> Self {
+    CompileOptions {
+      target: TargetOptions::default(),
+      opt_level: OptLevel::Default,
+      debug_info: false,
+      checks: HashSet::new(),
+      sanitizers: HashSet::new(),
+      include_dirs: Vec::new(),
+      std_dir: None,
+      dump: DumpOptions::default(),
+      lints: LintOptions::default(),
+      dep_file: None
+    }
+  }
+}
+
+/// Compile a bundle, returning the warnings on success, and all diagnostics on failure
+pub fn compile(input_path: &Path, output_path: &Path, compile_to: CompileTo, options: &CompileOptions) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+  compile_with(Box::new(FileSystem), input_path, vec![ (compile_to, output_path.to_path_buf()) ], options)
+}
+
+/// Check a bundle for errors without generating code, the diagnostics are returned like by compile
+pub fn check(input_path: &Path, options: &CompileOptions) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+  let mut session = Session::new(Box::new(FileSystem), options.clone());
+  session.parse(input_path)
+    .and_then(|bundle| session.resolve(bundle))
+    .and_then(|bundle| session.analyze(bundle))?;
+  Ok(session.warnings())
+}
+
+/// Compile a bundle whose root module is given as text, imports are still read from the file system
+pub fn compile_source(name: &str, text: &str, output_path: &Path, compile_to: CompileTo, options: &CompileOptions) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+  let mut provider = MemorySources::with_fallback(Box::new(FileSystem));
+  provider.add(name, text);
+  compile_with(Box::new(provider), Path::new(name), vec![ (compile_to, output_path.to_path_buf()) ], options)
+}
+
+/// Compile a bundle, reading all source files through provider, and writing each output requested
+pub fn compile_with(provider: Box<dyn SourceProvider>, input_path: &Path, outputs: Vec<(CompileTo, PathBuf)>, options: &CompileOptions) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+  let mut session = Session::new(provider, options.clone());
+  let mut bundle = session.parse(input_path)
+    .and_then(|bundle| session.resolve(bundle))
+    .and_then(|bundle| session.analyze(bundle))?;
+
+  // Code is only generated once, no matter how many outputs need it
+  let mut kinds = Vec::new();
+  for (compile_to, _) in outputs.iter() {
+    let kind = artifact_kind(compile_to);
+    if !kinds.contains(&kind) {
+      kinds.push(kind);
+    }
+  }
+  let artifacts = session.lower(&mut bundle, &kinds)?;
+
+  let targets: Vec<PathBuf> = outputs.iter().map(|(_, output_path)| output_path.clone()).collect();
+  let result = outputs.into_iter().try_for_each(|(compile_to, output_path)| {
+    let kind = artifact_kind(&compile_to);
+    let artifact = artifacts.iter().find(|artifact| artifact.kind == kind).unwrap();
+    match compile_to {
+      CompileTo::Executable(link_options) => {
+        link::link_object(&artifact.data, &output_path, &link_options, options.target.reloc_model, &options.sanitizers)
+      }
+      _ => write_artifact(&output_path, artifact)
+    }
+  }).and_then(|_| match &options.dep_file {
+    Some(dep_path) => dep::write(dep_path, &targets, &session.source_paths()),
+    None => Ok(())
+  });
+  match result {
+    Ok(()) => Ok(session.warnings()),
+    Err(error) => Err(session.fail(error))
+  }
+}
+
+fn artifact_kind(compile_to: &CompileTo) -> ArtifactKind {
+  match compile_to {
+    CompileTo::LLVMIr => ArtifactKind::LLVMIr,
+    CompileTo::LLVMBitcode => ArtifactKind::LLVMBitcode,
+    CompileTo::Assembly => ArtifactKind::Assembly,
+    CompileTo::Object | CompileTo::Executable(..) => ArtifactKind::Object
+  }
 }
 
-pub fn compile(input_path: &Path, output_path: &Path, compile_to: CompileTo, triple: Option<&str>) -> MRes<()> {
-  let parsed_repo = parse::parse_bundle(input_path)?;
-  let mut inst_collection = sema::analyze(&parsed_repo)?;
-  lower::compile(&mut inst_collection, output_path, compile_to, triple)
+fn write_artifact(path: &Path, artifact: &Artifact) -> MRes<()> {
+  fs::write(path, &artifact.data)
+    .map_err(|error| format!("{}: {}", path.display(), error))?;
+  Ok(())
 }
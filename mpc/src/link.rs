@@ -0,0 +1,139 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+use crate::util::*;
+use crate::sanitize::Sanitizer;
+use mpc_llvm::RelocModel;
+use std::{error, fmt, fs, io, process};
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fmt::Formatter;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+/// Options passed to the system linker driver
+
+#[derive(Clone, Debug)]
+pub struct LinkOptions {
+  /// Linker driver to invoke
+  pub linker: PathBuf,
+  /// Libraries to link against (-l)
+  pub libs: Vec<String>,
+  /// Library search directories (-L)
+  pub lib_dirs: Vec<PathBuf>,
+  /// Extra object files and archives to link in
+  pub objects: Vec<PathBuf>
+}
+
+impl Default for LinkOptions {
+  fn default() -> Self {
+    LinkOptions {
+      linker: PathBuf::from("cc"),
+      libs: Vec::new(),
+      lib_dirs: Vec::new(),
+      objects: Vec::new()
+    }
+  }
+}
+
+#[derive(Debug)]
+enum LinkError {
+  Spawn(PathBuf, std::io::Error),
+  Failed(PathBuf, ExitStatus)
+}
+
+impl fmt::Display for LinkError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      LinkError::Spawn(linker, error) => write!(f, "Failed to run linker {}: {}", linker.display(), error),
+      LinkError::Failed(linker, status) => write!(f, "Linker {} failed with {}", linker.display(), status)
+    }
+  }
+}
+
+impl error::Error for LinkError {}
+
+/// Directory only accessible by the current user, removed when dropped
+struct TempDir {
+  path: PathBuf
+}
+
+impl TempDir {
+  fn new() -> io::Result<TempDir> {
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    // NOTE: creating the directory fails if anything already exists at the
+    // path, so another user cannot plant a directory or symlink there
+    let mut attempt = 0;
+    loop {
+      let path = std::env::temp_dir().join(format!("mpc-{}-{}", process::id(), attempt));
+      match builder.create(&path) {
+        Ok(()) => return Ok(TempDir { path }),
+        Err(error) if error.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+        Err(error) => return Err(error)
+      }
+    }
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.path);
+  }
+}
+
+/// Link object code, and any extra inputs, into an executable via a temporary object file
+pub fn link_object(obj: &[u8], output_path: &Path, options: &LinkOptions, reloc_model: RelocModel, sanitizers: &HashSet<Sanitizer>) -> MRes<()> {
+  let dir = TempDir::new()
+    .map_err(|error| format!("Failed to create temporary directory: {}", error))?;
+  let stem = output_path.file_stem().unwrap_or_default();
+  let obj_path = dir.path.join(stem).with_extension("o");
+  fs::OpenOptions::new()
+    .write(true)
+    .create_new(true)
+    .open(&obj_path)
+    .and_then(|mut file| file.write_all(obj))
+    .map_err(|error| format!("{}: {}", obj_path.display(), error))?;
+  link(&obj_path, output_path, options, reloc_model, sanitizers)
+}
+
+/// Link an object file, and any extra inputs, into an executable
+fn link(obj_path: &Path, output_path: &Path, options: &LinkOptions, reloc_model: RelocModel, sanitizers: &HashSet<Sanitizer>) -> MRes<()> {
+  let mut args: Vec<OsString> = vec![ "-o".into(), output_path.into(), obj_path.into() ];
+  // Drivers default to position independent executables, which only PIC objects can be linked into
+  if reloc_model != RelocModel::Pic {
+    args.push("-no-pie".into());
+  }
+  // The driver knows where the sanitizer runtimes are, and what they depend on
+  let names: Vec<&str> = Sanitizer::ALL.iter()
+    .copied()
+    .filter(|name| {
+      let sanitizer: Sanitizer = name.parse().unwrap();
+      sanitizers.contains(&sanitizer) && sanitizer.has_runtime()
+    })
+    .collect();
+  if !names.is_empty() {
+    args.push(format!("-fsanitize={}", names.join(",")).into());
+  }
+  args.extend(options.objects.iter().map(|path| path.into()));
+  args.extend(options.lib_dirs.iter().map(|dir| {
+    let mut arg = OsString::from("-L");
+    arg.push(dir);
+    arg
+  }));
+  args.extend(options.libs.iter().map(|lib| format!("-l{}", lib).into()));
+
+  let status = Command::new(&options.linker)
+    .args(args)
+    .status()
+    .map_err(|error| LinkError::Spawn(options.linker.clone(), error))?;
+
+  match status.success() {
+    true => Ok(()),
+    false => Err(Box::new(LinkError::Failed(options.linker.clone(), status)))
+  }
+}
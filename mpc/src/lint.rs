@@ -0,0 +1,112 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+use crate::source::Span;
+use std::collections::HashMap;
+use std::fmt::{self, Formatter};
+use std::str::FromStr;
+
+/// Lints that can be reported as warnings
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Lint {
+  UnusedVariables,
+  UnusedImports,
+  UnreachableCode,
+  ShadowedLocals
+}
+
+impl Lint {
+  pub const ALL: [&'static str; 4] = [ "unused-variables", "unused-imports", "unreachable-code", "shadowed-locals" ];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Lint::UnusedVariables => "unused-variables",
+      Lint::UnusedImports => "unused-imports",
+      Lint::UnreachableCode => "unreachable-code",
+      Lint::ShadowedLocals => "shadowed-locals"
+    }
+  }
+
+  pub fn default_level(&self) -> LintLevel {
+    match self {
+      Lint::UnusedVariables |
+      Lint::UnusedImports |
+      Lint::UnreachableCode => LintLevel::Warn,
+      // Shadowing is often intentional
+      Lint::ShadowedLocals => LintLevel::Allow
+    }
+  }
+}
+
+impl fmt::Display for Lint {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+impl FromStr for Lint {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "unused-variables" => Ok(Lint::UnusedVariables),
+      "unused-imports" => Ok(Lint::UnusedImports),
+      "unreachable-code" => Ok(Lint::UnreachableCode),
+      "shadowed-locals" => Ok(Lint::ShadowedLocals),
+      _ => Err(format!("Unknown lint {}", s))
+    }
+  }
+}
+
+/// What to do when a lint is triggered
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LintLevel {
+  Allow,
+  Warn,
+  Deny
+}
+
+/// Lint levels for a compilation
+
+#[derive(Clone, Debug, Default)]
+pub struct LintOptions {
+  /// Levels overriding the defaults
+  pub levels: HashMap<Lint, LintLevel>,
+  /// Treat all warnings as errors
+  pub deny_warnings: bool
+}
+
+impl LintOptions {
+  pub fn level(&self, lint: Lint) -> LintLevel {
+    match self.levels.get(&lint).cloned().unwrap_or(lint.default_level()) {
+      LintLevel::Warn if self.deny_warnings => LintLevel::Deny,
+      level => level
+    }
+  }
+}
+
+/// Lint triggered by the program
+
+#[derive(Debug)]
+pub(crate) struct Warning {
+  pub lint: Lint,
+  pub span: Span,
+  pub message: String,
+  /// Related source location, with an explanation
+  pub label: Option<(Span, &'static str)>
+}
+
+impl Warning {
+  pub fn new(lint: Lint, span: Span, message: String) -> Warning {
+    Warning { lint, span, message, label: None }
+  }
+
+  pub fn labeled(mut self, span: Span, message: &'static str) -> Warning {
+    self.label = Some((span, message));
+    self
+  }
+}
@@ -0,0 +1,211 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+//
+// Runtime checks
+//
+// Each enabled check branches to a block calling a private routine that
+// prints the source location of the failing expression to stderr, then
+// aborts. The routine is marked cold, so that the optimizer moves the
+// failure paths out of the way.
+//
+
+use super::*;
+use crate::source::{Position, Span};
+use std::fmt::{self, Formatter};
+use std::str::FromStr;
+
+/// Runtime checks that can be generated
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Check {
+  Bounds,
+  Overflow,
+  Null,
+  DivZero
+}
+
+impl Check {
+  pub const ALL: [&'static str; 4] = [ "bounds", "overflow", "null", "div-zero" ];
+}
+
+impl fmt::Display for Check {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    let name = match self {
+      Check::Bounds => "bounds",
+      Check::Overflow => "overflow",
+      Check::Null => "null",
+      Check::DivZero => "div-zero"
+    };
+    write!(f, "{}", name)
+  }
+}
+
+impl FromStr for Check {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "bounds" => Ok(Check::Bounds),
+      "overflow" => Ok(Check::Overflow),
+      "null" => Ok(Check::Null),
+      "div-zero" => Ok(Check::DivZero),
+      _ => Err(format!("Unknown check {}", s))
+    }
+  }
+}
+
+fn is_signed(ty: &Ty) -> bool {
+  matches!(ty, Ty::Int8 | Ty::Int16 | Ty::Int32 | Ty::Int64 | Ty::Intn)
+}
+
+impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
+  /// Create the routine called by failed checks
+  pub(super) fn build_check_failed(&mut self) -> (llvm::Type<'ctx>, llvm::Value<'ctx>) {
+    let ty_int32 = self.context.ty_int32();
+    let ty_ptr = self.context.ty_ptr();
+    let func_ty = self.context.ty_function(self.context.ty_void(), &[ty_ptr], false);
+    let func = self.module.add_function(RefStr::new("mpc.check_failed").borrow_c(), func_ty);
+    func.set_linkage(llvm::Linkage::Private);
+    func.add_attribute("noreturn");
+    func.add_attribute("noinline");
+    func.add_attribute("cold");
+
+    // NOTE: the program might declare these itself, in which case they are
+    // already in the module, and adding them again would rename ours
+    let get_function = |name: &str, ty: llvm::Type<'ctx>| {
+      let name = RefStr::new(name);
+      self.module.get_function(name.borrow_c())
+        .unwrap_or_else(|| self.module.add_function(name.borrow_c(), ty))
+    };
+    let dprintf_ty = self.context.ty_function(ty_int32, &[ty_int32, ty_ptr], true);
+    let dprintf = get_function("dprintf", dprintf_ty);
+    let abort_ty = self.context.ty_function(self.context.ty_void(), &[], false);
+    let abort = get_function("abort", abort_ty);
+
+    let block = func.add_block();
+    self.enter_block(block);
+    let fd = self.build_int(&Ty::Int32, 2);
+    let format = self.build_string_lit(b"%s\n");
+    self.builder.call(dprintf_ty, dprintf, &[fd, format, func.get_param(0)]);
+    self.builder.call(abort_ty, abort, &[]);
+    self.builder.unreachable();
+
+    (func_ty, func)
+  }
+
+  /// Continue only if cond is false, otherwise report message at span
+  fn build_check(&mut self, span: Span, message: &str, cond: llvm::Value<'ctx>) {
+    let fail_block = self.new_block();
+    let ok_block = self.new_block();
+    self.exit_block_cond_br(cond, fail_block, ok_block);
+
+    self.enter_block(fail_block);
+    let file = self.sources.file(span.file);
+    let Position { line, column } = file.position(span.lo);
+    // Reported like UBSan would when sanitizing undefined behaviour
+    let message = match self.sanitize_undefined {
+      true => format!("{}:{}:{}: runtime error: {}", file.path.display(), line, column, message),
+      false => format!("{}:{}:{}: {}", file.path.display(), line, column, message)
+    };
+    let l_message = self.build_string_lit(message.as_bytes());
+    let (func_ty, func) = self.check_failed.unwrap();
+    self.builder.call(func_ty, func, &[l_message]);
+    self.builder.unreachable();
+
+    self.enter_block(ok_block);
+  }
+
+  /// Check an index into an array of type ty against its length
+  pub(super) fn check_bounds(&mut self, span: Span, ty: &Ty, index: llvm::Value<'ctx>) {
+    if !self.checks.contains(&Check::Bounds) {
+      return
+    }
+    let cnt = match self.tctx.final_ty(ty) {
+      Ty::Arr(cnt, _) => cnt,
+      _ => unreachable!()
+    };
+    // NOTE: indices are always Uintn
+    let cnt = self.build_int(&Ty::Uintn, cnt);
+    let cond = self.builder.icmp(llvm::LLVMIntUGE, index, cnt);
+    self.build_check(span, "index out of bounds", cond);
+  }
+
+  /// Check a pointer about to be dereferenced
+  pub(super) fn check_null(&mut self, span: Span, ptr: llvm::Value<'ctx>) {
+    if !self.checks.contains(&Check::Null) {
+      return
+    }
+    let ty = self.lower_intn();
+    let addr = self.builder.ptr_to_int(ty, ptr);
+    let null = self.context.const_int(ty, 0);
+    let cond = self.builder.icmp(llvm::LLVMIntEQ, addr, null);
+    self.build_check(span, "nil pointer dereference", cond);
+  }
+
+  /// Check the operands of an integer division or modulo
+  pub(super) fn check_div(&mut self, span: Span, ty: &Ty, lhs: llvm::Value<'ctx>, rhs: llvm::Value<'ctx>) {
+    let l_ty = self.lower_ty(ty);
+    if self.checks.contains(&Check::DivZero) {
+      let zero = self.context.const_int(l_ty, 0);
+      let cond = self.builder.icmp(llvm::LLVMIntEQ, rhs, zero);
+      self.build_check(span, "division by zero", cond);
+    }
+    // The only signed division that overflows is the minimum value by -1
+    if self.checks.contains(&Check::Overflow) && is_signed(&self.tctx.final_ty(ty)) {
+      let bits = self.size_of(l_ty) * 8;
+      let min = self.context.const_signed_int(l_ty, i64::MIN >> (64 - bits));
+      let minus_one = self.context.const_signed_int(l_ty, -1);
+      let lhs_min = self.builder.icmp(llvm::LLVMIntEQ, lhs, min);
+      let rhs_minus_one = self.builder.icmp(llvm::LLVMIntEQ, rhs, minus_one);
+      let cond = self.builder.and(lhs_min, rhs_minus_one);
+      self.build_check(span, "division overflowed", cond);
+    }
+  }
+
+  /// Integer addition, subtraction or multiplication, checked for overflow if enabled
+  pub(super) fn build_int_arith(&mut self, span: Span, ty: &Ty, op: BinOp, lhs: llvm::Value<'ctx>, rhs: llvm::Value<'ctx>) -> llvm::Value<'ctx> {
+    if !self.checks.contains(&Check::Overflow) {
+      return match op {
+        BinOp::Add => self.builder.add(lhs, rhs),
+        BinOp::Sub => self.builder.sub(lhs, rhs),
+        BinOp::Mul => self.builder.mul(lhs, rhs),
+        _ => unreachable!()
+      }
+    }
+
+    let signed = is_signed(&self.tctx.final_ty(ty));
+    let (name, message) = match (op, signed) {
+      (BinOp::Add, false) => ("llvm.uadd.with.overflow", "addition overflowed"),
+      (BinOp::Add, true) => ("llvm.sadd.with.overflow", "addition overflowed"),
+      (BinOp::Sub, false) => ("llvm.usub.with.overflow", "subtraction overflowed"),
+      (BinOp::Sub, true) => ("llvm.ssub.with.overflow", "subtraction overflowed"),
+      (BinOp::Mul, false) => ("llvm.umul.with.overflow", "multiplication overflowed"),
+      (BinOp::Mul, true) => ("llvm.smul.with.overflow", "multiplication overflowed"),
+      _ => unreachable!()
+    };
+    let l_ty = self.lower_ty(ty);
+    let (func_ty, func) = self.module.intrinsic(name, &[l_ty]);
+    let result = self.builder.call(func_ty, func, &[lhs, rhs]);
+    let cond = self.builder.extract_value(result, 1);
+    self.build_check(span, message, cond);
+    self.builder.extract_value(result, 0)
+  }
+
+  /// Integer negation, checked for overflow if enabled and the type is signed
+  pub(super) fn build_int_neg(&mut self, span: Span, ty: &Ty, arg: llvm::Value<'ctx>) -> llvm::Value<'ctx> {
+    if !self.checks.contains(&Check::Overflow) || !is_signed(&self.tctx.final_ty(ty)) {
+      return self.builder.neg(arg)
+    }
+
+    let l_ty = self.lower_ty(ty);
+    let zero = self.context.const_int(l_ty, 0);
+    let (func_ty, func) = self.module.intrinsic("llvm.ssub.with.overflow", &[l_ty]);
+    let result = self.builder.call(func_ty, func, &[zero, arg]);
+    let cond = self.builder.extract_value(result, 1);
+    self.build_check(span, "negation overflowed", cond);
+    self.builder.extract_value(result, 0)
+  }
+}
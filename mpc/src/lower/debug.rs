@@ -0,0 +1,313 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+//
+// Debug info generation
+//
+// Each function is described by a subprogram, its parameters, locals and
+// match bindings by variables pointing at their storage, and instructions are
+// given the location of the innermost expression they were generated for.
+// Types are described following the layout they were lowered to.
+//
+
+use super::*;
+use crate::source::{FileId, Position, Span};
+
+pub(super) struct DebugInfo<'ctx> {
+  builder: llvm::DIBuilder<'ctx>,
+  unit: llvm::Metadata<'ctx>,
+  unit_file: llvm::Metadata<'ctx>,
+  optimized: bool,
+
+  // Descriptors already created
+  files: HashMap<FileId, llvm::Metadata<'ctx>>,
+  types: HashMap<Ty, llvm::Metadata<'ctx>>,
+
+  // Subprogram of the function being lowered
+  subprogram: Option<llvm::Metadata<'ctx>>
+}
+
+impl<'ctx> DebugInfo<'ctx> {
+  pub(super) fn new(module: &llvm::Module<'ctx>, sources: &SourceMap, optimized: bool) -> Self {
+    let builder = module.di_builder();
+    // The root module is always the first file read
+    let unit_file = file_descriptor(&builder, sources.paths().next().unwrap());
+    let unit = builder.compile_unit(unit_file, concat!("mpc ", env!("CARGO_PKG_VERSION")), optimized);
+    DebugInfo {
+      builder,
+      unit,
+      unit_file,
+      optimized,
+      files: HashMap::new(),
+      types: HashMap::new(),
+      subprogram: None
+    }
+  }
+
+  pub(super) fn finalize(&self) {
+    self.builder.finalize();
+  }
+
+  /// Types have no source location, so they are declared at the start of the root module
+  fn decl(&self, scope: llvm::Metadata<'ctx>) -> llvm::DIDecl<'ctx> {
+    llvm::DIDecl { scope, file: self.unit_file, line: 0 }
+  }
+}
+
+fn file_descriptor<'ctx>(builder: &llvm::DIBuilder<'ctx>, path: &Path) -> llvm::Metadata<'ctx> {
+  let name = path.file_name().map_or(path.to_string_lossy(), |name| name.to_string_lossy());
+  let directory = path.parent().map_or_else(Default::default, |parent| parent.to_string_lossy());
+  builder.file(&name, &directory)
+}
+
+impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
+  fn di(&self) -> &DebugInfo<'ctx> {
+    self.debug.as_ref().unwrap()
+  }
+
+  fn di_mut(&mut self) -> &mut DebugInfo<'ctx> {
+    self.debug.as_mut().unwrap()
+  }
+
+  fn position(&self, span: Span) -> Position {
+    self.sources.file(span.file).position(span.lo)
+  }
+
+  fn debug_file(&mut self, file: FileId) -> llvm::Metadata<'ctx> {
+    if let Some(descriptor) = self.di().files.get(&file) {
+      return *descriptor
+    }
+    let descriptor = file_descriptor(&self.di().builder, &self.sources.file(file).path);
+    self.di_mut().files.insert(file, descriptor);
+    descriptor
+  }
+
+  /// Describe a function, and start attributing instructions to it
+  pub(super) fn debug_enter_func(&mut self, l_func: llvm::Value<'ctx>, span: Span, symbol: RefStr, is_export: IsExport, ty: &Ty) {
+    if self.debug.is_none() {
+      return
+    }
+
+    let ty = self.tctx.final_ty(ty);
+    let (params, _, ret_ty) = ty.unwrap_func();
+    let ret = match ret_ty {
+      Ty::Unit => None,
+      ret_ty => Some(self.debug_ty(ret_ty))
+    };
+    let params: Vec<llvm::Metadata<'ctx>> = params
+      .iter()
+      .map(|(_, ty)| self.debug_ty(ty))
+      .collect();
+
+    let file = self.debug_file(span.file);
+    let line = self.position(span).line;
+    let debug = self.di();
+    let subroutine_ty = debug.builder.subroutine_type(file, ret, &params);
+    // Show the path and type arguments encoded in the symbol name
+    let display_name = mangle::demangle(symbol.borrow_rs()).unwrap_or_else(|| symbol.to_string());
+    let decl = llvm::DIDecl { scope: file, file, line };
+    let subprogram = debug.builder.function(decl, &display_name, symbol.borrow_rs(),
+                                            subroutine_ty, is_export == IsExport::No, debug.optimized);
+    l_func.set_subprogram(subprogram);
+    self.di_mut().subprogram = Some(subprogram);
+    self.enter_location(span);
+  }
+
+  /// Stop attributing instructions to the current function
+  pub(super) fn debug_exit_func(&mut self) {
+    if let Some(debug) = self.debug.as_mut() {
+      debug.subprogram = None;
+      self.builder.set_debug_location(None);
+    }
+  }
+
+  /// Describe a parameter (with arg_no starting at 1), local variable, or binding stored at storage
+  pub(super) fn debug_variable(&mut self, storage: llvm::Value<'ctx>, span: Span, name: RefStr, ty: &Ty, arg_no: Option<usize>) {
+    let subprogram = match self.debug.as_ref().and_then(|debug| debug.subprogram) {
+      Some(subprogram) => subprogram,
+      None => return
+    };
+
+    let ty = self.debug_ty(ty);
+    let file = self.debug_file(span.file);
+    let Position { line, column } = self.position(span);
+    let block = self.builder.get_block().unwrap();
+    let debug = self.di();
+    let variable = match arg_no {
+      Some(arg_no) => debug.builder.parameter_variable(subprogram, name.borrow_rs(), arg_no, file, line, ty),
+      None => debug.builder.auto_variable(subprogram, name.borrow_rs(), file, line, ty)
+    };
+    let location = debug.builder.location(line, column, subprogram);
+    debug.builder.insert_declare(storage, variable, location, block);
+  }
+
+  /// Attribute the instructions built from now on to span, returning the previous location
+  pub(super) fn enter_location(&mut self, span: Span) -> Option<llvm::Metadata<'ctx>> {
+    let subprogram = self.debug.as_ref().and_then(|debug| debug.subprogram)?;
+
+    let outer = self.builder.debug_location();
+    let Position { line, column } = self.position(span);
+    let location = self.di().builder.location(line, column, subprogram);
+    self.builder.set_debug_location(Some(location));
+    outer
+  }
+
+  /// Go back to the location returned by enter_location
+  pub(super) fn exit_location(&mut self, outer: Option<llvm::Metadata<'ctx>>) {
+    if let Some(DebugInfo { subprogram: Some(_), .. }) = self.debug {
+      self.builder.set_debug_location(outer);
+    }
+  }
+
+  fn debug_ty(&mut self, ty: &Ty) -> llvm::Metadata<'ctx> {
+    let ty = self.tctx.final_ty(ty);
+    if let Some(descriptor) = self.di().types.get(&ty) {
+      return *descriptor
+    }
+
+    use Ty::*;
+    let descriptor = match &ty {
+      Bool => self.debug_basic_ty(&ty, llvm::DIEncoding::Boolean),
+      Uint8 | Uint16 | Uint32 | Uint64 | Uintn => self.debug_basic_ty(&ty, llvm::DIEncoding::Unsigned),
+      Int8 | Int16 | Int32 | Int64 | Intn => self.debug_basic_ty(&ty, llvm::DIEncoding::Signed),
+      Float | Double => self.debug_basic_ty(&ty, llvm::DIEncoding::Float),
+      Ptr(_, base_ty) => {
+        let base_ty = self.debug_ty(base_ty);
+        self.debug_ptr_ty(base_ty)
+      }
+      Func(params, _, ret_ty) => {
+        let ret = match ret_ty.as_ref() {
+          Unit => None,
+          ret_ty => Some(self.debug_ty(ret_ty))
+        };
+        let params: Vec<llvm::Metadata<'ctx>> = params
+          .iter()
+          .map(|(_, ty)| self.debug_ty(ty))
+          .collect();
+        let debug = self.di();
+        let subroutine_ty = debug.builder.subroutine_type(debug.unit_file, ret, &params);
+        self.debug_ptr_ty(subroutine_ty)
+      }
+      Arr(count, element) => {
+        let l_ty = self.lower_ty(&ty);
+        let (size, align) = (self.size_of(l_ty) * 8, self.align_of(l_ty) * 8);
+        let element = self.debug_ty(element);
+        self.di().builder.array_type(element, *count, size, align)
+      }
+      // Aggregates might refer to themselves through pointers, so a
+      // placeholder is used until their fields are described
+      Unit | Tuple(..) | StructRef(..) | UnionRef(..) | EnumRef(..) => {
+        let name = format!("{:?}", ty);
+        let debug = self.di();
+        let temporary = debug.builder.temporary_type(debug.decl(debug.unit), &name);
+        self.di_mut().types.insert(ty.clone(), temporary);
+        let descriptor = self.debug_aggregate_ty(&ty, &name, temporary);
+        self.di().builder.replace_temporary(temporary, descriptor);
+        descriptor
+      }
+      Var(..) | Error => unreachable!()
+    };
+
+    self.di_mut().types.insert(ty, descriptor);
+    descriptor
+  }
+
+  fn debug_basic_ty(&mut self, ty: &Ty, encoding: llvm::DIEncoding) -> llvm::Metadata<'ctx> {
+    let l_ty = self.lower_ty(ty);
+    let size = self.size_of(l_ty) * 8;
+    self.di().builder.basic_type(&format!("{:?}", ty), size, encoding)
+  }
+
+  fn debug_ptr_ty(&mut self, base_ty: llvm::Metadata<'ctx>) -> llvm::Metadata<'ctx> {
+    let l_ty = self.context.ty_ptr();
+    let size = self.size_of(l_ty) * 8;
+    self.di().builder.pointer_type(base_ty, size)
+  }
+
+  fn debug_aggregate_ty(&mut self, ty: &Ty, name: &str, scope: llvm::Metadata<'ctx>) -> llvm::Metadata<'ctx> {
+    let l_ty = self.lower_ty(ty);
+    match ty {
+      Ty::Unit => self.debug_struct_ty(name, scope, &[], l_ty),
+      Ty::Tuple(fields) => self.debug_struct_ty(name, scope, fields, l_ty),
+      Ty::StructRef(_, id) => {
+        let id = (id.0, self.tctx.final_type_args(&id.1));
+        let (_, fields) = self.insts.get(&id).unwrap().unwrap_struct();
+        self.debug_struct_ty(name, scope, fields, l_ty)
+      }
+      Ty::UnionRef(_, id) => {
+        let id = (id.0, self.tctx.final_type_args(&id.1));
+        let (_, fields) = self.insts.get(&id).unwrap().unwrap_union();
+        let members = self.debug_members(scope, fields, None);
+        let (size, align) = (self.size_of(l_ty) * 8, self.align_of(l_ty) * 8);
+        let debug = self.di();
+        debug.builder.union_type(debug.decl(debug.unit), name, size, align, &members)
+      }
+      Ty::EnumRef(_, id) => {
+        let id = (id.0, self.tctx.final_type_args(&id.1));
+        let (_, variants) = self.insts.get(&id).unwrap().unwrap_enum();
+        self.debug_enum_ty(name, scope, variants, l_ty)
+      }
+      _ => unreachable!()
+    }
+  }
+
+  fn debug_struct_ty(&mut self, name: &str, scope: llvm::Metadata<'ctx>, fields: &[(RefStr, Ty)], l_ty: llvm::Type<'ctx>) -> llvm::Metadata<'ctx> {
+    let members = self.debug_members(scope, fields, Some(l_ty));
+    let (size, align) = (self.size_of(l_ty) * 8, self.align_of(l_ty) * 8);
+    let debug = self.di();
+    debug.builder.struct_type(debug.decl(debug.unit), name, size, align, &members)
+  }
+
+  /// Describe fields at their offsets inside l_struct, or all at offset zero if it's a union
+  fn debug_members(&mut self, scope: llvm::Metadata<'ctx>, fields: &[(RefStr, Ty)], l_struct: Option<llvm::Type<'ctx>>) -> Vec<llvm::Metadata<'ctx>> {
+    let mut members = Vec::new();
+    for (index, (name, ty)) in fields.iter().enumerate() {
+      let l_ty = self.lower_ty(ty);
+      let (size, align) = (self.size_of(l_ty) * 8, self.align_of(l_ty) * 8);
+      let offset = l_struct.map_or(0, |l_struct| self.target.offset_of(l_struct, index) * 8);
+      let ty = self.debug_ty(ty);
+      let debug = self.di();
+      members.push(debug.builder.member_type(debug.decl(scope), name.borrow_rs(), size, align, offset, ty));
+    }
+    members
+  }
+
+  fn debug_enum_ty(&mut self, name: &str, scope: llvm::Metadata<'ctx>, variants: &[Variant], l_ty: llvm::Type<'ctx>) -> llvm::Metadata<'ctx> {
+    // The discriminant is described as an enumeration of the variant names
+    let tag_ty = self.debug_ty(&Ty::Int32);
+    let debug = self.di();
+    let enumerators: Vec<llvm::Metadata<'ctx>> = variants
+      .iter()
+      .enumerate()
+      .map(|(index, variant)| match variant {
+        Variant::Unit(name) | Variant::Struct(name, _) => debug.builder.enumerator(name.borrow_rs(), index as i64)
+      })
+      .collect();
+    let tag_ty = debug.builder.enumeration_type(debug.decl(scope), "tag", 32, 32, &enumerators, tag_ty);
+    let mut members = vec![ debug.builder.member_type(debug.decl(scope), "tag", 32, 32, 0, tag_ty) ];
+
+    // Followed by the fields of struct-like variants, sharing the same storage
+    let mut variant_tys = Vec::new();
+    for variant in variants.iter() {
+      if let Variant::Struct(name, fields) = variant {
+        let l_variant_ty = self.lower_ty(&Ty::Tuple(fields.clone()));
+        let (size, align) = (self.size_of(l_variant_ty) * 8, self.align_of(l_variant_ty) * 8);
+        let variant_ty = self.debug_struct_ty(name.borrow_rs(), scope, fields, l_variant_ty);
+        let debug = self.di();
+        variant_tys.push(debug.builder.member_type(debug.decl(scope), name.borrow_rs(), size, align, 0, variant_ty));
+      }
+    }
+    let (size, align) = (self.size_of(l_ty) * 8, self.align_of(l_ty) * 8);
+    if !variant_tys.is_empty() {
+      let offset = self.target.offset_of(l_ty, 1) * 8;
+      let debug = self.di();
+      let data_ty = debug.builder.union_type(debug.decl(scope), "data", size - offset, align, &variant_tys);
+      members.push(debug.builder.member_type(debug.decl(scope), "data", size - offset, align, offset, data_ty));
+    }
+
+    let debug = self.di();
+    debug.builder.struct_type(debug.decl(debug.unit), name, size, align, &members)
+  }
+}
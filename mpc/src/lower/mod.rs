@@ -4,32 +4,56 @@
  */
 
 use crate::*;
+use crate::dump::Dumper;
 use crate::sema::*;
-use crate::parse::{DefId,BinOp,UnOp};
+use crate::source::Span;
+use crate::parse::{DefId,BinOp,UnOp,IsExport,IsMut};
 use mpc_llvm as llvm;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+mod check;
+mod debug;
+
+pub use check::Check;
+use debug::DebugInfo;
 
 pub fn compile(collection: &mut Collection,
-               output: &Path,
-               compile_to: CompileTo,
-               triple: Option<&str>) -> MRes<()> {
+               sources: &SourceMap,
+               kinds: &[ArtifactKind],
+               options: &CompileOptions,
+               dumper: &mut Dumper) -> MRes<Vec<Artifact>> {
+  let target = llvm::Target::new(&options.target, options.opt_level)?;
   let context = llvm::Context::new();
   let mut ctx = LowerCtx::new(&mut collection.tctx,
                               &collection.insts,
+                              sources,
                               &context,
                               RefStr::new(""),
-                              triple);
+                              target,
+                              options);
 
   ctx.lower_defs();
-  if let Some(_) = option_env!("MPC_SPEW") {
-    ctx.module.dump();
+  if let Some(debug) = ctx.debug.as_ref() {
+    debug.finalize();
+  }
+  dumper.dump(DumpKind::Llvm, |w| w.write_all(ctx.module.print_to_string().as_bytes()))?;
+  // Instrumentation comes last, so that it only covers accesses surviving optimization
+  let mut passes: Vec<&str> = options.opt_level.pipeline().into_iter().collect();
+  if options.sanitizers.contains(&Sanitizer::Address) {
+    passes.push("asan-module");
   }
-  match compile_to {
-    CompileTo::LLVMIr => ctx.target.write_llvm_ir(ctx.module, output)?,
-    CompileTo::Assembly => ctx.target.write_machine_code(ctx.module, true, output)?,
-    CompileTo::Object => ctx.target.write_machine_code(ctx.module, false, output)?,
-  };
-  Ok(())
+  if !passes.is_empty() {
+    ctx.target.run_passes(&ctx.module, &passes.join(","))?;
+  }
+  Ok(kinds.iter().map(|kind| {
+    let data = match kind {
+      ArtifactKind::LLVMIr => ctx.target.emit_llvm_ir(&ctx.module),
+      ArtifactKind::LLVMBitcode => ctx.target.emit_bitcode(&ctx.module),
+      ArtifactKind::Assembly => ctx.target.emit_machine_code(&ctx.module, true),
+      ArtifactKind::Object => ctx.target.emit_machine_code(&ctx.module, false)
+    };
+    Artifact { kind: *kind, data }
+  }).collect())
 }
 
 /// Semantics of a type
@@ -42,15 +66,29 @@ enum Semantics {
 struct LowerCtx<'a, 'ctx> {
   tctx: &'a mut TVarCtx,
   insts: &'a HashMap<(DefId, Vec<Ty>), Inst>,
+  sources: &'a SourceMap,
 
   // Target machine
   target: llvm::Target,
+  frame_pointers: bool,
+  sanitize_address: bool,
+  sanitize_undefined: bool,
 
   // LLVM handles
   context: &'ctx llvm::Context,
   builder: llvm::Builder<'ctx>,
   module: llvm::Module<'ctx>,
 
+  // Debug info, if enabled
+  debug: Option<DebugInfo<'ctx>>,
+
+  // Runtime checks, and the routine called when they fail
+  checks: HashSet<Check>,
+  check_failed: Option<(llvm::Type<'ctx>, llvm::Value<'ctx>)>,
+
+  // Set while lowering an lvalue whose address is taken, without accessing it
+  addr_only: bool,
+
   l_func: Option<llvm::Value<'ctx>>,
   l_alloca_block: Option<llvm::Block<'ctx>>,
 
@@ -68,6 +106,9 @@ struct LowerCtx<'a, 'ctx> {
   locals: Vec<llvm::Value<'ctx>>,
   bindings: Vec<llvm::Value<'ctx>>,
 
+  // Names and types of the bindings, for debug info
+  binding_defs: &'a [(Span, RefStr, IsMut, Ty)],
+
   // Break and continue blocks
   break_to: Vec<llvm::Block<'ctx>>,
   continue_to: Vec<llvm::Block<'ctx>>,
@@ -79,30 +120,47 @@ struct LowerCtx<'a, 'ctx> {
 impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
   fn new(tctx: &'a mut TVarCtx,
          insts: &'a HashMap<(DefId, Vec<Ty>), Inst>,
+         sources: &'a SourceMap,
          context: &'ctx llvm::Context,
          name: RefStr,
-         triple: Option<&str>) -> Self {
-
-    let target = if let Some(triple) = triple {
-      llvm::Target::from_triplet(triple).unwrap()
-    } else {
-      llvm::Target::native()
-    };
+         target: llvm::Target,
+         options: &CompileOptions) -> Self {
 
     let builder = context.builder();
     let module = context.module(name.borrow_c());
     module.set_target(&target);
+    // Undefined behaviour is caught by generating every runtime check
+    let mut checks = options.checks.clone();
+    if options.sanitizers.contains(&Sanitizer::Undefined) {
+      checks.extend([ Check::Bounds, Check::Overflow, Check::Null, Check::DivZero ]);
+    }
+    let debug = if options.debug_info {
+      Some(DebugInfo::new(&module, sources, options.opt_level.is_optimized()))
+    } else {
+      None
+    };
 
     LowerCtx {
       tctx,
       insts,
+      sources,
 
       target,
+      frame_pointers: options.target.frame_pointers,
+      sanitize_address: options.sanitizers.contains(&Sanitizer::Address),
+      sanitize_undefined: options.sanitizers.contains(&Sanitizer::Undefined),
 
       context,
       builder,
       module,
 
+      debug,
+
+      checks,
+      check_failed: None,
+
+      addr_only: false,
+
       l_func: None,
       l_alloca_block: None,
 
@@ -115,6 +173,8 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
       locals: Vec::new(),
       bindings: Vec::new(),
 
+      binding_defs: &[],
+
       break_to: Vec::new(),
       continue_to: Vec::new(),
       break_vals: Vec::new()
@@ -184,7 +244,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
 
   fn lower_union(&mut self, l_params: &[llvm::Type<'ctx>]) -> Vec<llvm::Type<'ctx>> {
     // NOTE: this special case is needed otherwise bad things (NULL-derefs happen)
-    if l_params.len() == 0 {
+    if l_params.is_empty() {
       return vec![]
     }
 
@@ -239,14 +299,14 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
       StructRef(_, id) |
       UnionRef(_, id) |
       EnumRef(_, id) => {
-        self.get_type(&id)
+        self.get_type(id)
       }
       Ptr(..) |
       Func(..) => {
         self.context.ty_ptr()
       }
       Arr(count, element) => {
-        let element = self.lower_ty(&element);
+        let element = self.lower_ty(element);
         self.context.ty_array(element, *count)
       }
       Unit => {
@@ -314,24 +374,45 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
     // Pass 1: Create LLVM values for each definition
     for (id, def) in self.insts.iter() {
       let l_value = match def {
-        Inst::Data { name, init, .. } => {
+        Inst::Data { symbol, init, .. } => {
           let ty = self.const_init_ty(init);
-          self.module.add_global(name.borrow_c(), ty)
+          self.module.add_global(symbol.borrow_c(), ty)
         }
         Inst::ExternData { name, ty, .. } => {
           let ty = self.lower_ty(ty);
           self.module.add_global(name.borrow_c(), ty)
         }
-        Inst::Func { name, ty, .. } |
+        Inst::Func { symbol: name, ty, .. } |
         Inst::ExternFunc { name, ty, .. } => {
           let ty = self.lower_func_ty(ty);
           self.module.add_function(name.borrow_c(), ty)
         }
         _ => continue
       };
+      if let (true, Inst::Func { .. }) = (self.frame_pointers, def) {
+        l_value.add_string_attribute("frame-pointer", "all");
+      }
+      // Only functions carrying the attribute are instrumented
+      if let (true, Inst::Func { .. }) = (self.sanitize_address, def) {
+        l_value.add_attribute("sanitize_address");
+      }
+      // Only exported definitions are visible outside of the object file
+      match def {
+        Inst::Func { is_export: IsExport::No, .. } |
+        Inst::Data { is_export: IsExport::No, .. } => l_value.set_linkage(llvm::Linkage::Internal),
+        _ => ()
+      }
+      if let Inst::Data { is_mut: IsMut::No, .. } = def {
+        l_value.set_constant(true);
+      }
 
       self.values.insert(id.clone(), l_value);
     }
+    // NOTE: this has to come after pass 1, as it refers to libc functions the
+    // program might declare itself
+    if !self.checks.is_empty() {
+      self.check_failed = Some(self.build_check_failed());
+    }
     // Pass 2: Lower initializers and function bodies
     for (id, def) in self.insts.iter() {
       match def {
@@ -340,8 +421,9 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
           let init = self.lower_const_val(init);
           global.set_initializer(init);
         }
-        Inst::Func { params, locals, body: Some(body), .. } => {
+        Inst::Func { span, symbol, is_export, ty, params, locals, bindings, body: Some(body), .. } => {
           self.l_func = Some(self.get_value(id));
+          self.debug_enter_func(self.l_func.unwrap(), *span, *symbol, *is_export, ty);
 
           // Create prelude block for allocas
           self.l_alloca_block = Some(self.new_block());
@@ -352,9 +434,10 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
             = self.ty_semantics(body.ty()) { 1 } else { 0 };
 
           self.params.clear();
-          for (_, ty) in params.iter() {
+          for (index, (span, name, _, ty)) in params.iter().enumerate() {
             let l_alloca = self.allocate_local(ty);
             self.params.push(l_alloca);
+            self.debug_variable(l_alloca, *span, *name, ty, Some(index + 1));
 
             match self.ty_semantics(ty) {
               Semantics::Void => (),
@@ -368,13 +451,15 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
 
           // Allocate locals
           self.locals.clear();
-          for (_, ty) in locals.iter() {
+          for (span, name, _, ty) in locals.iter() {
             let l_alloca = self.allocate_local(ty);
             self.locals.push(l_alloca);
+            self.debug_variable(l_alloca, *span, *name, ty, None);
           }
 
           // Clear bindings
           self.bindings.clear();
+          self.binding_defs = bindings;
 
           // Create LLVM function body
           let body_block = self.new_block();
@@ -385,6 +470,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
           // Add branch from allocas to body
           self.enter_block(self.l_alloca_block.unwrap());
           self.exit_block_br(body_block);
+          self.debug_exit_func();
         }
         _ => ()
       }
@@ -492,6 +578,14 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
   /// Expressions
 
   fn lower_lvalue(&mut self, lvalue: &LValue) -> llvm::Value<'ctx> {
+    // Instructions are attributed to the innermost expression they are built for
+    let outer = self.enter_location(lvalue.span());
+    let val = self.lower_lvalue_at(lvalue);
+    self.exit_location(outer);
+    val
+  }
+
+  fn lower_lvalue_at(&mut self, lvalue: &LValue) -> llvm::Value<'ctx> {
     match lvalue {
       LValue::DataRef { id, .. } => {
         self.get_value(&(*id, vec![]))
@@ -512,15 +606,13 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
         let storage = self.allocate_local(ty);
         for (index, element) in elements.iter().enumerate() {
           let dest = self.build_gep(ty, storage, index);
-          self.lower_rvalue(element)
-            .map(|val| self.build_store(element.ty(), dest, val));
+          if let Some(val) = self.lower_rvalue(element) { self.build_store(element.ty(), dest, val) }
         }
         storage
       }
       LValue::UnionLit { ty, field, .. } => {
         let storage = self.allocate_local(ty);
-        self.lower_rvalue(field)
-          .map(|val| self.build_store(field.ty(), storage, val));
+        if let Some(val) = self.lower_rvalue(field) { self.build_store(field.ty(), storage, val) }
         storage
       }
       LValue::TupleLit { ty, fields, .. } |
@@ -528,8 +620,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
         let storage = self.allocate_local(ty);
         for (index, field) in fields.iter().enumerate() {
           let dest = self.build_gep(ty, storage, index);
-          self.lower_rvalue(field)
-            .map(|val| self.build_store(field.ty(), dest, val));
+          if let Some(val) = self.lower_rvalue(field) { self.build_store(field.ty(), dest, val) }
         }
         storage
       }
@@ -557,8 +648,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
 
         for (index, field) in fields.iter().enumerate() {
           let dest = self.build_gep(&data_ty, data_ptr, index);
-          self.lower_rvalue(field)
-            .map(|val| self.build_store(field.ty(), dest, val));
+          if let Some(val) = self.lower_rvalue(field) { self.build_store(field.ty(), dest, val) }
         }
 
         storage
@@ -570,18 +660,34 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
       LValue::UnionDot { arg, .. } => {
         self.lower_lvalue(arg)
       }
-      LValue::Index { arg, idx, .. } => {
+      LValue::Index { span, arg, idx, .. } => {
         let base = self.lower_lvalue(arg);
         let index = self.lower_rvalue(idx).unwrap();
+        self.check_bounds(*span, arg.ty(), index);
         self.build_index(arg.ty(), base, index)
       }
-      LValue::Ind { arg, .. } => {
-        self.lower_rvalue(arg).unwrap()
+      LValue::Ind { span, arg, .. } => {
+        // NOTE: &(*ptr).field is used for computing offsets from nil
+        let addr_only = self.addr_only;
+        let ptr = self.lower_rvalue(arg).unwrap();
+        if !addr_only {
+          self.check_null(*span, ptr);
+        }
+        ptr
       }
     }
   }
 
   fn lower_rvalue(&mut self, rvalue: &RValue) -> Option<llvm::Value<'ctx>> {
+    let outer = self.enter_location(rvalue.span());
+    let addr_only = std::mem::replace(&mut self.addr_only, false);
+    let val = self.lower_rvalue_at(rvalue);
+    self.addr_only = addr_only;
+    self.exit_location(outer);
+    val
+  }
+
+  fn lower_rvalue_at(&mut self, rvalue: &RValue) -> Option<llvm::Value<'ctx>> {
     match rvalue {
       RValue::Unit { .. } => {
         None
@@ -613,8 +719,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
         let mut l_args = Vec::new();
         args.iter()
           .for_each(|arg| {
-            self.lower_rvalue(arg)
-              .map(|val| l_args.push(val));
+            if let Some(val) = self.lower_rvalue(arg) { l_args.push(val) }
           });
 
         match self.ty_semantics(ty) {
@@ -634,21 +739,22 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
         }
       }
       RValue::Adr { arg, .. } => {
+        self.addr_only = true;
         Some(self.lower_lvalue(arg))
       }
-      RValue::Un { op, arg, .. } => {
+      RValue::Un { span, op, arg, .. } => {
         let val = self.lower_rvalue(arg).unwrap();
-        Some(self.build_un(arg.ty(), *op, val))
+        Some(self.build_un(*span, arg.ty(), *op, val))
       }
-      RValue::Cast { ty, arg } => {
+      RValue::Cast { ty, arg, .. } => {
         let val = self.lower_rvalue(arg).unwrap();
         Some(self.build_cast(ty, arg.ty(), val))
       }
-      RValue::Bin { op, lhs, rhs, .. } => {
+      RValue::Bin { span, op, lhs, rhs, .. } => {
         let ty = lhs.ty();
         let lhs = self.lower_rvalue(lhs).unwrap();
         let rhs = self.lower_rvalue(rhs).unwrap();
-        Some(self.build_bin(ty, *op, lhs, rhs))
+        Some(self.build_bin(*span, ty, *op, lhs, rhs))
       }
       RValue::LNot { .. } |
       RValue::LAnd { .. } |
@@ -684,19 +790,18 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
       }
       RValue::As { lhs, rhs, .. } => {
         let dest = self.lower_lvalue(lhs);
-        self.lower_rvalue(rhs)
-          .map(|src| self.build_store(lhs.ty(), dest, src));
+        if let Some(src) = self.lower_rvalue(rhs) { self.build_store(lhs.ty(), dest, src) }
         // Void value
         None
       }
-      RValue::Rmw { op, lhs, rhs, .. } => {
+      RValue::Rmw { span, op, lhs, rhs, .. } => {
         // LHS: We need both the address and value
         let dest_addr = self.lower_lvalue(lhs);
         let lhs_val = self.build_load(lhs.ty(), dest_addr).unwrap();
         // RHS: We need only the value
         let rhs_val = self.lower_rvalue(rhs).unwrap();
         // Then we can perform the computation and do the store
-        let tmp_val = self.build_bin(lhs.ty(), *op, lhs_val, rhs_val);
+        let tmp_val = self.build_bin(*span, lhs.ty(), *op, lhs_val, rhs_val);
         self.build_store(lhs.ty(), dest_addr, tmp_val);
         // Void value
         None
@@ -710,7 +815,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
         // Unreachable value
         self.build_unreachable(ty)
       }
-      RValue::Break { ty, arg } => {
+      RValue::Break { ty, arg, .. } => {
         // Lower break argument
         let val = self.lower_rvalue(arg);
         // Add break source to loop
@@ -726,7 +831,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
         self.build_unreachable(ty)
       }
       RValue::Return { ty, arg, .. } => {
-        let retval = self.lower_rvalue(&*arg);
+        let retval = self.lower_rvalue(arg);
         self.exit_block_ret(arg.ty(), retval);
         // Throw away code until next useful location
         let dead_block = self.new_block();
@@ -737,8 +842,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
       RValue::Let { index, init, .. } => {
         let l_local = self.locals[*index];
         if let Some(init) = init {
-          self.lower_rvalue(init)
-            .map(|val| self.build_store(init.ty(), l_local, val));
+          if let Some(val) = self.lower_rvalue(init) { self.build_store(init.ty(), l_local, val) }
         }
         // Void value
         None
@@ -810,15 +914,16 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
 
         // Merge break values
         self.enter_block(end_block);
-        if vals.len() > 0 { // Loop was actually broken
+        if !vals.is_empty() { // Loop was actually broken
           self.build_phi(ty, &vals, &blocks)
         } else { // Infinite loop without breaks
           self.build_unreachable(ty)
         }
       }
       RValue::Match { ty, cond, cases, .. } => {
-        let start_block = self.builder.get_block().unwrap();
+        // NOTE: lowering the condition can create new blocks, e.g. for checks
         let addr = self.lower_rvalue(cond).unwrap();
+        let start_block = self.builder.get_block().unwrap();
 
         let end_block = self.new_block();
 
@@ -837,8 +942,10 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
           self.enter_block(block);
           if let Some(binding) = binding {
             assert_eq!(*binding, self.bindings.len());
-            let binding = self.build_gep(cond.ty(), addr, 1);
-            self.bindings.push(binding);
+            let l_binding = self.build_gep(cond.ty(), addr, 1);
+            self.bindings.push(l_binding);
+            let (span, name, _, ty) = &self.binding_defs[*binding];
+            self.debug_variable(l_binding, *span, *name, ty, None);
           }
           values.push(self.lower_rvalue(val));
           blocks.push(self.builder.get_block().unwrap());
@@ -846,7 +953,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
         }
 
         // There are no cases => we are still in the start block and can just yield None
-        if tag_to_block.len() == 0 { return None }
+        if tag_to_block.is_empty() { return None }
 
         // Go back to the start block and build a switch for the multi-way branch
         // NOTE: matches are always exhaustive, and LLVM always wants a default case,
@@ -860,6 +967,10 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
         self.enter_block(end_block);
         self.build_phi(ty, &values, &blocks)
       }
+      RValue::Error { .. } => {
+        // NOTE: programs with errors are never lowered
+        unreachable!()
+      }
     }
   }
 
@@ -898,7 +1009,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
     let context = &self.context;
     let index = self.string_lits.len();
 
-    *self.string_lits.raw_entry_mut().from_key(data).or_insert_with(|| {
+    *self.string_lits.entry(data.to_vec()).or_insert_with(|| {
       // Create name
       let name = RefStr::new(&format!(".str.{}", index));
 
@@ -911,9 +1022,11 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
       // Set initializer
       // NOTE: for now these are NUL-terminated
       global.set_initializer(context.const_null_terminated_string(data));
+      global.set_linkage(llvm::Linkage::Private);
+      global.set_constant(true);
 
-      (data.to_vec(), global)
-    }).1
+      global
+    })
   }
 
   fn build_bool(&mut self, val: bool) -> llvm::Value<'ctx> {
@@ -1025,7 +1138,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
   fn ty_semantics(&mut self, ty: &Ty) -> Semantics {
     use Ty::*;
 
-    match self.tctx.final_ty(&ty) {
+    match self.tctx.final_ty(ty) {
       Unit => Semantics::Void,
       Bool | Uint8 | Int8 | Uint16 |
       Int16 |Uint32 | Int32 | Uint64 |
@@ -1059,18 +1172,18 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
       Semantics::Value => {
         let ty = self.lower_ty(ty);
         let values: Vec<llvm::Value<'ctx>> = values
-          .into_iter()
+          .iter()
           .map(|x| x.unwrap())
           .collect();
-        Some(self.builder.phi(ty, &values, &blocks))
+        Some(self.builder.phi(ty, &values, blocks))
       }
       Semantics::Addr => {
         let ty = self.context.ty_ptr();
         let values: Vec<llvm::Value<'ctx>> = values
-          .into_iter()
+          .iter()
           .map(|x| x.unwrap())
           .collect();
-        Some(self.builder.phi(ty, &values, &blocks))
+        Some(self.builder.phi(ty, &values, blocks))
       }
     }
   }
@@ -1089,7 +1202,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
     self.builder.call(func_ty, func_ptr, args)
   }
 
-  fn build_un(&mut self, ty: &Ty, op: UnOp, arg: llvm::Value<'ctx>) -> llvm::Value<'ctx> {
+  fn build_un(&mut self, span: Span, ty: &Ty, op: UnOp, arg: llvm::Value<'ctx>) -> llvm::Value<'ctx> {
     use Ty::*;
     use UnOp::*;
 
@@ -1098,7 +1211,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
         arg
       }
       (UMinus, Uint8 | Int8 | Uint16 | Int16 | Uint32 | Int32 | Uint64 | Int64 | Uintn | Intn) => {
-        self.builder.neg(arg)
+        self.build_int_neg(span, ty, arg)
       }
       (UMinus, Float | Double) => {
         self.builder.fneg(arg)
@@ -1182,14 +1295,14 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
     }
   }
 
-  fn build_bin(&mut self, ty: &Ty, op: BinOp, lhs: llvm::Value<'ctx>, rhs: llvm::Value<'ctx>) -> llvm::Value<'ctx> {
+  fn build_bin(&mut self, span: Span, ty: &Ty, op: BinOp, lhs: llvm::Value<'ctx>, rhs: llvm::Value<'ctx>) -> llvm::Value<'ctx> {
     use Ty::*;
     use BinOp::*;
 
     match (op, self.tctx.final_ty(ty)) {
       // Integer multiply
       (Mul, Uint8 | Int8 | Uint16 | Int16 | Uint32 | Int32 | Uint64 | Int64 | Uintn | Intn) => {
-        self.builder.mul(lhs, rhs)
+        self.build_int_arith(span, ty, op, lhs, rhs)
       }
       // Floating point multiply
       (Mul, Float | Double) => {
@@ -1197,10 +1310,12 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
       }
       // Unsigned integer divide
       (Div, Uint8 | Uint16 | Uint32 | Uint64 | Uintn) => {
+        self.check_div(span, ty, lhs, rhs);
         self.builder.udiv(lhs, rhs)
       }
       // Signed integer divide
       (Div, Int8 | Int16 | Int32 | Int64 | Intn) => {
+        self.check_div(span, ty, lhs, rhs);
         self.builder.sdiv(lhs, rhs)
       }
       // Floating point divide
@@ -1209,15 +1324,17 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
       }
       // Unsigned integer modulo
       (Mod, Uint8 | Uint16 | Uint32 | Uint64 | Uintn) => {
+        self.check_div(span, ty, lhs, rhs);
         self.builder.urem(lhs, rhs)
       }
       // Signed integer modulo
       (Mod, Int8 | Int16 | Int32 | Int64 | Intn) => {
+        self.check_div(span, ty, lhs, rhs);
         self.builder.srem(lhs, rhs)
       }
       // Integer addition
       (Add, Uint8 | Int8 | Uint16 | Int16 | Uint32 | Int32 | Uint64 | Int64 | Uintn | Intn) => {
-        self.builder.add(lhs, rhs)
+        self.build_int_arith(span, ty, op, lhs, rhs)
       }
       // Floating point addition
       (Add, Float | Double) => {
@@ -1225,7 +1342,7 @@ impl<'a, 'ctx> LowerCtx<'a, 'ctx> {
       }
       // Integer substraction
       (Sub, Uint8 | Int8 | Uint16 | Int16 | Uint32 | Int32 | Uint64 | Int64 | Uintn | Intn) => {
-        self.builder.sub(lhs, rhs)
+        self.build_int_arith(span, ty, op, lhs, rhs)
       }
       // Floating point substraction
       (Sub, Float | Double) => {
@@ -5,49 +5,469 @@
 
 use clap::*;
 use mpc::*;
-use std::path::Path;
+use std::collections::HashSet;
+use std::env;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Artifacts that can be requested with --emit
+const EMIT_KINDS: [&str; 5] = [ "obj", "asm", "llvm-ir", "llvm-bc", "link" ];
 
 fn main() {
   let args = clap::app_from_crate!()
     .arg(Arg::with_name("input")
       .help("Input file")
-      .required(true)
+      .required_unless("explain")
       .index(1))
+    .arg(Arg::with_name("objects")
+      .help("Additional object files to link")
+      .multiple(true)
+      .index(2))
     .arg(Arg::with_name("assembly")
       .short("S")
       .help("Generate assembly"))
     .arg(Arg::with_name("llvm-ir")
-      .short("L")
+      .long("llvm-ir")
       .help("Generate LLVM IR"))
+    .arg(Arg::with_name("object")
+      .short("c")
+      .help("Generate an object file without linking"))
+    .arg(Arg::with_name("emit")
+      .long("emit")
+      .help("Artifacts to generate, each optionally followed by =<path>")
+      .takes_value(true)
+      .multiple(true)
+      .use_delimiter(true)
+      .validator(|emit| {
+        let kind = emit.split('=').next().unwrap();
+        match EMIT_KINDS.contains(&kind) {
+          true => Ok(()),
+          false => Err(format!("unknown artifact kind {}, expected one of {}", kind, EMIT_KINDS.join(", ")))
+        }
+      }))
+    .arg(Arg::with_name("libs")
+      .short("l")
+      .help("Link against library")
+      .takes_value(true)
+      .number_of_values(1)
+      .multiple(true))
+    .arg(Arg::with_name("lib-dirs")
+      .short("L")
+      .help("Add directory to the library search path")
+      .takes_value(true)
+      .number_of_values(1)
+      .multiple(true))
+    .arg(Arg::with_name("linker")
+      .long("linker")
+      .help("Linker driver to use (defaults to cc)")
+      .takes_value(true))
     .arg(Arg::with_name("output")
       .short("o")
       .long("output")
-      .help("Output file")
-      .required(true)
+      .help("Output file, or the base name of outputs if several are generated")
+      .takes_value(true))
+    .arg(Arg::with_name("dep-file")
+      .long("dep-file")
+      .help("Write the source files the outputs depend on to a Makefile")
       .takes_value(true))
     .arg(Arg::with_name("target")
       .long("target")
       .help("Target triplet")
       .takes_value(true))
+    .arg(Arg::with_name("target-cpu")
+      .long("target-cpu")
+      .help("CPU to generate code for, native selects the host CPU")
+      .takes_value(true))
+    .arg(Arg::with_name("target-features")
+      .long("target-features")
+      .help("Target features to enable (+feature) or disable (-feature), separated by commas")
+      .takes_value(true)
+      .allow_hyphen_values(true))
+    .arg(Arg::with_name("relocation-model")
+      .long("relocation-model")
+      .help("Relocation model")
+      .takes_value(true)
+      .possible_values(&["static", "pic", "dynamic-no-pic"])
+      .default_value("pic"))
+    .arg(Arg::with_name("code-model")
+      .long("code-model")
+      .help("Code model")
+      .takes_value(true)
+      .possible_values(&["tiny", "small", "kernel", "medium", "large"]))
+    .arg(Arg::with_name("frame-pointers")
+      .long("frame-pointers")
+      .help("Keep frame pointers in every function"))
+    .arg(Arg::with_name("opt-level")
+      .short("O")
+      .help("Optimization level")
+      .takes_value(true)
+      .possible_values(&["0", "1", "2", "3", "s"]))
+    .arg(Arg::with_name("debug-info")
+      .short("g")
+      .help("Generate debug info"))
+    .arg(Arg::with_name("checks")
+      .long("checks")
+      .help("Runtime checks to generate, all or none")
+      .takes_value(true)
+      .multiple(true)
+      .use_delimiter(true)
+      .possible_values(&["all", "none"])
+      .possible_values(&Check::ALL))
+    .arg(Arg::with_name("sanitize")
+      .long("sanitize")
+      .help("Instrument the program with sanitizers")
+      .takes_value(true)
+      .multiple(true)
+      .use_delimiter(true)
+      .possible_values(&Sanitizer::ALL))
+    .args(&front_end_args())
+    .arg(Arg::with_name("explain")
+      .long("explain")
+      .help("Explain an error code")
+      .takes_value(true))
+    .subcommand(SubCommand::with_name("check")
+      .about("Check a program for errors without generating code")
+      .arg(Arg::with_name("input")
+        .help("Input file")
+        .required(true)
+        .index(1))
+      .args(&front_end_args()))
+    .subcommand(SubCommand::with_name("demangle")
+      .about("Demangle symbol names, or every symbol name in stdin if none are given")
+      .arg(Arg::with_name("symbols")
+        .help("Symbol names")
+        .multiple(true)))
+    .setting(AppSettings::SubcommandsNegateReqs)
     .get_matches();
 
-  let compile_to = if args.occurrences_of("llvm-ir") > 0 {
-    CompileTo::LLVMIr
-  } else if args.occurrences_of("assembly") > 0 {
-    CompileTo::Assembly
-  } else {
-    CompileTo::Object
+  if let Some(code) = args.value_of("explain") {
+    match explain(code) {
+      Some(explanation) => print!("{}", explanation),
+      None => {
+        eprintln!("error: {} is not a valid error code", code);
+        std::process::exit(1);
+      }
+    }
+    return;
+  }
+
+  if let Some(args) = args.subcommand_matches("demangle") {
+    match args.values_of("symbols") {
+      Some(symbols) => {
+        for symbol in symbols {
+          println!("{}", demangle(symbol).as_deref().unwrap_or(symbol));
+        }
+      }
+      None => {
+        for line in io::stdin().lines() {
+          match line {
+            Ok(line) => println!("{}", demangle_text(&line)),
+            Err(error) => {
+              eprintln!("error: {}", error);
+              std::process::exit(1);
+            }
+          }
+        }
+      }
+    }
+    return;
+  }
+
+  if let Some(args) = args.subcommand_matches("check") {
+    let input_path = Path::new(args.value_of_os("input").unwrap());
+    let result = check(input_path, &compile_options(args));
+    finish(args, result);
+  }
+
+  let input_path = Path::new(args.value_of_os("input").unwrap());
+  let output_path = args.value_of_os("output").map(PathBuf::from);
+
+  // Artifacts to generate, along with their paths if given
+  let emit: Vec<(&str, Option<PathBuf>)> = match args.values_of("emit") {
+    Some(values) => values
+      .map(|value| match value.split_once('=') {
+        Some((kind, path)) => (kind, Some(PathBuf::from(path))),
+        None => (value, None)
+      })
+      .collect(),
+    None => {
+      let extension = output_path.as_ref().and_then(|path| path.extension()).and_then(|ext| ext.to_str());
+      let is_object_path = matches!(extension, Some("o") | Some("obj"));
+      let kind = if args.occurrences_of("llvm-ir") > 0 {
+        "llvm-ir"
+      } else if args.occurrences_of("assembly") > 0 {
+        "asm"
+      } else if args.occurrences_of("object") > 0 || is_object_path {
+        "obj"
+      } else {
+        "link"
+      };
+      vec![ (kind, None) ]
+    }
   };
 
-  let status = match compile(Path::new(args.value_of_os("input").unwrap()),
-                             Path::new(args.value_of_os("output").unwrap()),
-                             compile_to, args.value_of("target")) {
-    Ok(()) => 0,
-    Err(error) => {
-      eprintln!("{} :(", error);
-      1
+  let mut link_options = LinkOptions::default();
+  if let Some(linker) = args.value_of_os("linker") {
+    link_options.linker = PathBuf::from(linker);
+  }
+  if let Some(libs) = args.values_of("libs") {
+    link_options.libs = libs.map(String::from).collect();
+  }
+  if let Some(lib_dirs) = args.values_of_os("lib-dirs") {
+    link_options.lib_dirs = lib_dirs.map(PathBuf::from).collect();
+  }
+  if let Some(objects) = args.values_of_os("objects") {
+    link_options.objects = objects.map(PathBuf::from).collect();
+  }
+
+  // A single artifact is written to the output path as is, otherwise the
+  // names are derived from it, or from the input file if not specified
+  let single = emit.len() == 1;
+  let base_path = output_path.clone()
+    .unwrap_or_else(|| PathBuf::from(input_path.file_stem().unwrap_or_default()));
+  let outputs = emit
+    .into_iter()
+    .map(|(kind, path)| {
+      let (compile_to, extension) = match kind {
+        "obj" => (CompileTo::Object, "o"),
+        "asm" => (CompileTo::Assembly, "s"),
+        "llvm-ir" => (CompileTo::LLVMIr, "ll"),
+        "llvm-bc" => (CompileTo::LLVMBitcode, "bc"),
+        _ => (CompileTo::Executable(link_options.clone()), "")
+      };
+      let path = path.unwrap_or_else(|| match &output_path {
+        Some(output_path) if single => output_path.clone(),
+        _ => base_path.with_extension(extension)
+      });
+      (compile_to, path)
+    })
+    .collect();
+
+  let result = compile_with(Box::new(FileSystem), input_path, outputs, &compile_options(&args));
+  finish(&args, result);
+}
+
+/// Options shared by code generation and check mode
+fn compile_options(args: &ArgMatches) -> CompileOptions {
+  // Later lint flags override earlier ones
+  let mut lint_flags = Vec::new();
+  for (name, level) in [("warn", LintLevel::Warn), ("allow", LintLevel::Allow), ("deny", LintLevel::Deny)] {
+    if let (Some(indices), Some(lints)) = (args.indices_of(name), args.values_of(name)) {
+      lint_flags.extend(indices.zip(lints).map(|(index, lint)| (index, lint.parse::<Lint>().unwrap(), level)));
+    }
+  }
+  lint_flags.sort_by_key(|(index, _, _)| *index);
+
+  // Directories from the command line take precedence over MPC_PATH
+  let mut include_dirs: Vec<PathBuf> = args.values_of_os("include-dirs")
+    .map(|dirs| dirs.map(PathBuf::from).collect())
+    .unwrap_or_default();
+  if let Some(path) = env::var_os("MPC_PATH") {
+    include_dirs.extend(env::split_paths(&path).filter(|dir| !dir.as_os_str().is_empty()));
+  }
+
+  // Later checks flags add to earlier ones, apart from none
+  let mut checks = HashSet::new();
+  for check in args.values_of("checks").into_iter().flatten() {
+    match check {
+      "all" => checks.extend(Check::ALL.iter().map(|check| check.parse::<Check>().unwrap())),
+      "none" => checks.clear(),
+      _ => { checks.insert(check.parse().unwrap()); }
     }
+  }
+
+  CompileOptions {
+    target: TargetOptions {
+      triple: args.value_of("target").map(String::from),
+      cpu: args.value_of("target-cpu").map(String::from),
+      features: args.value_of("target-features").map(String::from),
+      reloc_model: match args.value_of("relocation-model") {
+        Some("static") => RelocModel::Static,
+        Some("dynamic-no-pic") => RelocModel::DynamicNoPic,
+        _ => RelocModel::Pic
+      },
+      code_model: match args.value_of("code-model") {
+        Some("tiny") => CodeModel::Tiny,
+        Some("small") => CodeModel::Small,
+        Some("kernel") => CodeModel::Kernel,
+        Some("medium") => CodeModel::Medium,
+        Some("large") => CodeModel::Large,
+        _ => CodeModel::Default
+      },
+      frame_pointers: args.occurrences_of("frame-pointers") > 0
+    },
+    opt_level: match args.value_of("opt-level") {
+      Some("0") => OptLevel::O0,
+      Some("1") => OptLevel::O1,
+      Some("2") => OptLevel::O2,
+      Some("3") => OptLevel::O3,
+      Some("s") => OptLevel::Os,
+      _ => OptLevel::Default
+    },
+    debug_info: args.occurrences_of("debug-info") > 0,
+    checks,
+    sanitizers: args.values_of("sanitize")
+      .map(|sanitizers| sanitizers.map(|sanitizer| sanitizer.parse().unwrap()).collect())
+      .unwrap_or_default(),
+    include_dirs,
+    std_dir: args.value_of_os("std-dir").map(PathBuf::from),
+    dump: DumpOptions {
+      kinds: args.values_of("dump")
+        .map(|kinds| kinds.map(|kind| kind.parse().unwrap()).collect())
+        .unwrap_or_default(),
+      path: args.value_of_os("dump-file").map(PathBuf::from)
+    },
+    lints: LintOptions {
+      levels: lint_flags.into_iter().map(|(_, lint, level)| (lint, level)).collect(),
+      deny_warnings: args.occurrences_of("deny-warnings") > 0
+    },
+    dep_file: args.value_of_os("dep-file").map(PathBuf::from)
+  }
+}
+
+/// Report the diagnostics of a compilation, then exit with 1 if it failed
+fn finish(args: &ArgMatches, result: std::result::Result<Vec<Diagnostic>, Vec<Diagnostic>>) -> ! {
+  let (status, diagnostics) = match result {
+    Ok(diagnostics) => (0, diagnostics),
+    Err(diagnostics) => (1, diagnostics)
   };
+  let _ = report(&diagnostics,
+                 args.value_of("error-format").unwrap().parse().unwrap(),
+                 args.value_of("color").unwrap().parse().unwrap(),
+                 args.value_of("error-limit").unwrap().parse().unwrap());
 
   std::process::exit(status);
 }
+
+/// Arguments shared by code generation and check mode
+fn front_end_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+  vec![
+    Arg::with_name("include-dirs")
+      .short("I")
+      .help("Add directory to the module search path")
+      .takes_value(true)
+      .number_of_values(1)
+      .multiple(true),
+    Arg::with_name("std-dir")
+      .long("std-dir")
+      .help("Standard library directory")
+      .takes_value(true),
+    Arg::with_name("dump")
+      .long("dump")
+      .help("Dump intermediate representations")
+      .takes_value(true)
+      .multiple(true)
+      .use_delimiter(true)
+      .possible_values(&DumpKind::ALL),
+    Arg::with_name("dump-file")
+      .long("dump-file")
+      .help("Write dumps to file instead of stderr")
+      .takes_value(true),
+    Arg::with_name("color")
+      .long("color")
+      .help("Colorize diagnostics")
+      .takes_value(true)
+      .possible_values(&ColorChoice::ALL)
+      .default_value("auto"),
+    Arg::with_name("error-format")
+      .long("error-format")
+      .help("Format of diagnostics")
+      .takes_value(true)
+      .possible_values(&ErrorFormat::ALL)
+      .default_value("human"),
+    Arg::with_name("error-limit")
+      .long("error-limit")
+      .help("Maximum number of errors to report, 0 for no limit")
+      .takes_value(true)
+      .default_value("20")
+      .validator(|limit| limit.parse::<usize>().map(|_| ()).map_err(|error| error.to_string())),
+    Arg::with_name("warn")
+      .short("W")
+      .help("Report lint as a warning")
+      .takes_value(true)
+      .number_of_values(1)
+      .multiple(true)
+      .possible_values(&Lint::ALL),
+    Arg::with_name("allow")
+      .short("A")
+      .help("Do not report lint")
+      .takes_value(true)
+      .number_of_values(1)
+      .multiple(true)
+      .possible_values(&Lint::ALL),
+    Arg::with_name("deny")
+      .short("D")
+      .help("Report lint as an error")
+      .takes_value(true)
+      .number_of_values(1)
+      .multiple(true)
+      .possible_values(&Lint::ALL),
+    Arg::with_name("deny-warnings")
+      .long("deny-warnings")
+      .help("Report all warnings as errors")
+  ]
+}
+
+fn report(diagnostics: &[Diagnostic], format: ErrorFormat, color: ColorChoice, limit: usize) -> io::Result<()> {
+  let mut stderr = io::stderr().lock();
+
+  // Only errors count towards the limit, warnings are always shown
+  let errors = diagnostics.iter().filter(|diagnostic| diagnostic.severity == Severity::Error).count();
+  let limit = if limit == 0 { errors } else { errors.min(limit) };
+  let mut seen = 0;
+  let shown: Vec<&Diagnostic> = diagnostics
+    .iter()
+    .filter(|diagnostic| {
+      if diagnostic.severity == Severity::Error {
+        seen += 1;
+        seen <= limit
+      } else {
+        true
+      }
+    })
+    .collect();
+
+  match format {
+    ErrorFormat::Human => {
+      let color = color.for_stderr();
+      for diagnostic in shown.iter() {
+        diagnostic.render(&mut stderr, color)?;
+        writeln!(stderr)?;
+      }
+      let warnings = diagnostics.len() - errors;
+      if limit < errors {
+        Diagnostic::error(format!("aborting after {} errors, {} more not shown", limit, errors - limit))
+          .render(&mut stderr, color)?;
+      } else if errors > 1 {
+        Diagnostic::error(format!("aborting due to {} errors", errors))
+          .render(&mut stderr, color)?;
+      } else if errors == 0 && warnings > 1 {
+        Diagnostic::warning(format!("{} warnings emitted", warnings))
+          .render(&mut stderr, color)?;
+      }
+
+      // Point at the long-form explanations of the errors reported
+      let mut codes: Vec<&str> = shown
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .filter_map(|diagnostic| diagnostic.code)
+        .filter(|code| explain(code).is_some())
+        .collect();
+      codes.sort();
+      codes.dedup();
+      match codes.as_slice() {
+        [] => Ok(()),
+        [code] => writeln!(stderr, "For more information about this error, try `mpc --explain {}`.", code),
+        [code, ..] => {
+          writeln!(stderr, "Some errors have detailed explanations: {}.", codes.join(", "))?;
+          writeln!(stderr, "For more information about an error, try `mpc --explain {}`.", code)
+        }
+      }
+    }
+    ErrorFormat::Json => {
+      shown
+        .iter()
+        .try_for_each(|diagnostic| diagnostic.render_json(&mut stderr))
+    }
+  }
+}
@@ -0,0 +1,297 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+//
+// Symbol name mangling
+//
+// Functions and data are given symbol names encoding the path of the
+// module they are defined in, their name, and the type arguments of the
+// instance, so that definitions with the same name in different modules,
+// or different instances of a generic function never collide.
+//
+// symbol   = "_M" path [ "I" { type } "E" ]
+// path     = "N" { ident } "E"
+// ident    = <length in decimal> <characters>
+// type     = "b" Bool | "h" Uint8 | "a" Int8 | "t" Uint16 | "s" Int16
+//          | "j" Uint32 | "i" Int32 | "m" Uint64 | "l" Int64
+//          | "o" Uintn | "n" Intn | "f" Float | "d" Double | "v" ()
+//          | "S" path [ "I" { type } "E" ]          struct
+//          | "U" path [ "I" { type } "E" ]          union
+//          | "V" path [ "I" { type } "E" ]          enum
+//          | "P" type                               *T
+//          | "M" type                               *mut T
+//          | "A" <length in decimal> "_" type       [N]T
+//          | "T" { ident type } "E"                 tuple
+//          | "F" { ident type } [ "z" ] "E" type    function, z marking varargs
+//
+// For example vec::push<Int32> becomes _MN3vec4pushEIiE.
+//
+// Extern and exported definitions, and the main function of the root module
+// keep their names, as they need to be visible to C code.
+//
+
+use crate::parse::{DefId, IsMut, Repository};
+use crate::sema::Ty;
+use crate::util::*;
+use std::fmt::Write;
+
+/// Symbol name of an instance of a definition
+pub fn mangle(repo: &Repository, id: &(DefId, Vec<Ty>), name: RefStr) -> String {
+  let mut symbol = String::from("_M");
+  mangle_path(repo, id, name, &mut symbol);
+  symbol
+}
+
+fn mangle_path(repo: &Repository, (def_id, type_args): &(DefId, Vec<Ty>), name: RefStr, out: &mut String) {
+  out.push('N');
+  for crumb in repo.module_path(*def_id).iter().chain(std::iter::once(&name)) {
+    mangle_ident(crumb.borrow_rs(), out);
+  }
+  out.push('E');
+  if !type_args.is_empty() {
+    out.push('I');
+    for ty in type_args.iter() {
+      mangle_ty(repo, ty, out);
+    }
+    out.push('E');
+  }
+}
+
+fn mangle_ident(ident: &str, out: &mut String) {
+  write!(out, "{}{}", ident.len(), ident).unwrap();
+}
+
+fn mangle_ty(repo: &Repository, ty: &Ty, out: &mut String) {
+  match ty {
+    Ty::Bool => out.push('b'),
+    Ty::Uint8 => out.push('h'),
+    Ty::Int8 => out.push('a'),
+    Ty::Uint16 => out.push('t'),
+    Ty::Int16 => out.push('s'),
+    Ty::Uint32 => out.push('j'),
+    Ty::Int32 => out.push('i'),
+    Ty::Uint64 => out.push('m'),
+    Ty::Int64 => out.push('l'),
+    Ty::Uintn => out.push('o'),
+    Ty::Intn => out.push('n'),
+    Ty::Float => out.push('f'),
+    Ty::Double => out.push('d'),
+    Ty::Unit => out.push('v'),
+    Ty::StructRef(name, id) => {
+      out.push('S');
+      mangle_path(repo, id, *name, out);
+    }
+    Ty::UnionRef(name, id) => {
+      out.push('U');
+      mangle_path(repo, id, *name, out);
+    }
+    Ty::EnumRef(name, id) => {
+      out.push('V');
+      mangle_path(repo, id, *name, out);
+    }
+    Ty::Ptr(IsMut::No, ty) => {
+      out.push('P');
+      mangle_ty(repo, ty, out);
+    }
+    Ty::Ptr(IsMut::Yes, ty) => {
+      out.push('M');
+      mangle_ty(repo, ty, out);
+    }
+    Ty::Arr(cnt, ty) => {
+      write!(out, "A{}_", cnt).unwrap();
+      mangle_ty(repo, ty, out);
+    }
+    Ty::Tuple(fields) => {
+      out.push('T');
+      for (name, ty) in fields.iter() {
+        mangle_ident(name.borrow_rs(), out);
+        mangle_ty(repo, ty, out);
+      }
+      out.push('E');
+    }
+    Ty::Func(params, varargs, ret_ty) => {
+      out.push('F');
+      for (name, ty) in params.iter() {
+        mangle_ident(name.borrow_rs(), out);
+        mangle_ty(repo, ty, out);
+      }
+      if *varargs {
+        out.push('z');
+      }
+      out.push('E');
+      mangle_ty(repo, ret_ty, out);
+    }
+    // Instances only exist for fully inferred types
+    Ty::Var(..) | Ty::Error => unreachable!()
+  }
+}
+
+/// Demangle a symbol name, None is returned if it wasn't produced by mangle
+pub fn demangle(symbol: &str) -> Option<String> {
+  let mut demangler = Demangler { input: symbol.strip_prefix("_M")?.as_bytes(), out: String::new() };
+  demangler.path()?;
+  match demangler.input.is_empty() {
+    true => Some(demangler.out),
+    false => None
+  }
+}
+
+/// Demangle every symbol name found in text, leaving the rest untouched
+pub fn demangle_text(text: &str) -> String {
+  let is_symbol_char = |ch: char| ch.is_ascii_alphanumeric() || ch == '_';
+  let mut out = String::new();
+  let mut rest = text;
+  while let Some(start) = rest.find("_M") {
+    // Symbols can only start at the beginning of a word
+    let at_word_start = rest[..start].chars().next_back().is_none_or(|ch| !is_symbol_char(ch));
+    let end = rest[start..].find(|ch| !is_symbol_char(ch)).map_or(rest.len(), |len| start + len);
+    out.push_str(&rest[..start]);
+    match demangle(&rest[start..end]) {
+      Some(demangled) if at_word_start => out.push_str(&demangled),
+      _ => out.push_str(&rest[start..end])
+    }
+    rest = &rest[end..];
+  }
+  out.push_str(rest);
+  out
+}
+
+struct Demangler<'a> {
+  input: &'a [u8],
+  out: String
+}
+
+impl<'a> Demangler<'a> {
+  fn eat(&mut self, ch: u8) -> bool {
+    match self.input.first() {
+      Some(first) if *first == ch => {
+        self.input = &self.input[1..];
+        true
+      }
+      _ => false
+    }
+  }
+
+  fn next(&mut self) -> Option<u8> {
+    let (first, rest) = self.input.split_first()?;
+    self.input = rest;
+    Some(*first)
+  }
+
+  fn number(&mut self) -> Option<usize> {
+    let len = self.input.iter().take_while(|ch| ch.is_ascii_digit()).count();
+    let number = std::str::from_utf8(&self.input[..len]).ok()?.parse().ok()?;
+    self.input = &self.input[len..];
+    Some(number)
+  }
+
+  fn ident(&mut self) -> Option<()> {
+    let len = self.number()?;
+    if len > self.input.len() {
+      return None
+    }
+    self.out.push_str(std::str::from_utf8(&self.input[..len]).ok()?);
+    self.input = &self.input[len..];
+    Some(())
+  }
+
+  fn path(&mut self) -> Option<()> {
+    if !self.eat(b'N') {
+      return None
+    }
+    let mut first = true;
+    while !self.eat(b'E') {
+      if !first {
+        self.out.push_str("::");
+      }
+      first = false;
+      self.ident()?;
+    }
+    if self.eat(b'I') {
+      self.out.push('<');
+      let mut first = true;
+      while !self.eat(b'E') {
+        if !first {
+          self.out.push_str(", ");
+        }
+        first = false;
+        self.ty()?;
+      }
+      self.out.push('>');
+    }
+    Some(())
+  }
+
+  fn fields(&mut self, varargs: bool) -> Option<()> {
+    let mut first = true;
+    loop {
+      if self.eat(b'E') {
+        return Some(())
+      }
+      if !first {
+        self.out.push_str(", ");
+      }
+      first = false;
+      if varargs && self.eat(b'z') {
+        self.out.push_str("...");
+        return self.eat(b'E').then_some(())
+      }
+      self.ident()?;
+      self.out.push_str(": ");
+      self.ty()?;
+    }
+  }
+
+  fn ty(&mut self) -> Option<()> {
+    let name = match self.next()? {
+      b'b' => "Bool",
+      b'h' => "Uint8",
+      b'a' => "Int8",
+      b't' => "Uint16",
+      b's' => "Int16",
+      b'j' => "Uint32",
+      b'i' => "Int32",
+      b'm' => "Uint64",
+      b'l' => "Int64",
+      b'o' => "Uintn",
+      b'n' => "Intn",
+      b'f' => "Float",
+      b'd' => "Double",
+      b'v' => "()",
+      b'S' | b'U' | b'V' => return self.path(),
+      b'P' => {
+        self.out.push('*');
+        return self.ty()
+      }
+      b'M' => {
+        self.out.push_str("*mut ");
+        return self.ty()
+      }
+      b'A' => {
+        let cnt = self.number()?;
+        if !self.eat(b'_') {
+          return None
+        }
+        write!(self.out, "[{}]", cnt).unwrap();
+        return self.ty()
+      }
+      b'T' => {
+        self.out.push('(');
+        self.fields(false)?;
+        self.out.push(')');
+        return Some(())
+      }
+      b'F' => {
+        self.out.push_str("Function(");
+        self.fields(true)?;
+        self.out.push_str(") -> ");
+        return self.ty()
+      }
+      _ => return None
+    };
+    self.out.push_str(name);
+    Some(())
+  }
+}
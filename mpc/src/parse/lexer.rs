@@ -10,11 +10,10 @@ use std::str::FromStr;
 
 pub struct Lexer<'input> {
   keywords: HashMap<&'static str, Token>,
+  file: FileId,
   input: &'input str,
   begin: usize,
-  end: usize,
-  line: usize,
-  column: usize
+  end: usize
 }
 
 #[derive(Clone, Debug)]
@@ -63,6 +62,9 @@ pub enum Token {
   KwConst,          // const
   KwData,           // data
   KwImport,         // import
+  KwFrom,           // from
+  KwPub,            // pub
+  KwExport,         // export
   KwExtern,         // extern
   LParen,           // (
   RParen,           // )
@@ -112,7 +114,7 @@ pub enum Token {
 }
 
 impl<'input> Iterator for Lexer<'input> {
-  type Item = Result<(Location, Token, Location), Error>;
+  type Item = Result<(usize, Token, usize), Error>;
 
   fn next(&mut self) -> Option<Self::Item> {
     self.read_token()
@@ -120,7 +122,7 @@ impl<'input> Iterator for Lexer<'input> {
 }
 
 impl<'input> Lexer<'input> {
-  pub fn new(input: &'input str) -> Self {
+  pub fn new(file: FileId, input: &'input str) -> Self {
     let keywords = HashMap::from([
       ("Bool", Token::TyBool),
       ("Uint8", Token::TyUint8),
@@ -158,50 +160,42 @@ impl<'input> Lexer<'input> {
       ("const", Token::KwConst),
       ("data", Token::KwData),
       ("import", Token::KwImport),
+      ("from", Token::KwFrom),
+      ("pub", Token::KwPub),
+      ("export", Token::KwExport),
       ("extern", Token::KwExtern),
     ]);
 
     Lexer {
       keywords,
+      file,
       input,
       begin: 0,
-      end: 0,
-      line: 1,
-      column: 1
+      end: 0
     }
   }
 
-  pub fn read_token(&mut self) -> Option<Result<(Location, Token, Location), Error>> {
+  pub fn read_token(&mut self) -> Option<Result<(usize, Token, usize), Error>> {
     loop {
       // Save beginning of token
       self.begin = self.end;
-      // Save starting location of token
-      let start_loc = self.location();
 
       // Read character or bail on EOF
-      let byte = if let Some(byte) = self.consume_byte() {
-        byte
-      } else {
-        return None
-      };
+      let byte = self.consume_byte()?;
 
       // Decide next state after seeing initial char
       let token = match byte {
         // Whitespaces
-        b'\n' => {
-          self.handle_newline();
-          continue
-        }
-        b'\r' | b'\t' | b' ' => continue,
+        b'\n' | b'\r' | b'\t' | b' ' => continue,
         // C string literals
         b'c' if self.consume(b'"') => loop {
           match self.consume_byte() {
             Some(b'\n') | None => {
-              return Some(Err(Error::UnterminatedStr(self.location())))
+              return Some(Err(Error::UnterminatedStr(self.span())))
             }
             Some(b'"') => {
               let s = self.slice();
-              match unescape(start_loc, &s[2..s.len() - 1]) {
+              match unescape(self.span(), &s[2..s.len() - 1]) {
                 Ok(v) => break Token::CStrLit(v),
                 Err(err) => return Some(Err(err)),
               }
@@ -232,11 +226,11 @@ impl<'input> Lexer<'input> {
         b'"' => loop {
           match self.consume_byte() {
             Some(b'\n') | None => {
-              return Some(Err(Error::UnterminatedStr(self.location())))
+              return Some(Err(Error::UnterminatedStr(self.span())))
             }
             Some(b'"') => {
               let s = self.slice();
-              match unescape(start_loc, &s[1..s.len() - 1]) {
+              match unescape(self.span(), &s[1..s.len() - 1]) {
                 Ok(v) => break Token::StrLit(v),
                 Err(err) => return Some(Err(err)),
               }
@@ -248,16 +242,16 @@ impl<'input> Lexer<'input> {
         b'\'' => loop {
           match self.consume_byte() {
             Some(b'\n') | None => {
-              return Some(Err(Error::UnterminatedChar(self.location())))
+              return Some(Err(Error::UnterminatedChar(self.span())))
             }
             Some(b'\'') => {
               let s = self.slice();
-              match unescape(start_loc, &s[1..s.len() - 1]) {
+              match unescape(self.span(), &s[1..s.len() - 1]) {
                 Ok(v) if v.len() == 1 => {
                   break Token::IntLit(v[0] as usize)
                 }
                 Ok(_) => {
-                  return Some(Err(Error::InvalidChar(start_loc)))
+                  return Some(Err(Error::InvalidChar(self.span())))
                 }
                 Err(err) => {
                   return Some(Err(err))
@@ -355,11 +349,7 @@ impl<'input> Lexer<'input> {
           Some(b'/') => {
             self.consume_byte();
             while match self.consume_byte() {
-              Some(b'\n') => {
-                self.handle_newline();
-                false
-              }
-              None => false,
+              Some(b'\n') | None => false,
               Some(_) => true
             } {}
             continue
@@ -368,16 +358,12 @@ impl<'input> Lexer<'input> {
             self.consume_byte();
             while match self.consume_byte() {
               None => {
-                return Some(Err(Error::UnterminatedComment(self.location())))
+                return Some(Err(Error::UnterminatedComment(self.span())))
               }
               Some(b'*') if matches!(self.peek_byte(), Some(b'/')) => {
                 self.consume_byte();
                 false
               }
-              Some(b'\n') => {
-                self.handle_newline();
-                true
-              }
               _ => true
             } {}
             continue
@@ -443,26 +429,16 @@ impl<'input> Lexer<'input> {
             Token::Colon
           }
         }
-        _ => return Some(Err(Error::UnknownToken(self.location())))
+        _ => return Some(Err(Error::UnknownToken(self.span())))
       };
 
-      return Some(Ok((start_loc, token, self.location())))
+      return Some(Ok((self.begin, token, self.end)))
     }
   }
 
-  fn handle_newline(&mut self) {
-    self.line += 1;
-    self.column = 1;
-  }
-
   fn read_ident(&mut self) -> Token {
-    loop {
-      match self.peek_byte() {
-        Some(b'_' | b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9') => {
-          self.consume_byte();
-        }
-        _ => break
-      }
+    while let Some(b'_' | b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9') = self.peek_byte() {
+      self.consume_byte();
     }
 
     if let Some(kw) = self.keywords.get(self.slice()) {
@@ -473,25 +449,15 @@ impl<'input> Lexer<'input> {
   }
 
   fn read_binary(&mut self) -> Token {
-    loop {
-      match self.peek_byte() {
-        Some(b'0'..=b'1') => {
-          self.consume_byte();
-        }
-        _ => break
-      }
+    while let Some(b'0'..=b'1') = self.peek_byte() {
+      self.consume_byte();
     }
     Token::IntLit(usize::from_str_radix(&self.slice()[2..], 2).unwrap())
   }
 
   fn read_octal(&mut self) -> Token {
-    loop {
-      match self.peek_byte() {
-        Some(b'0'..=b'7') => {
-          self.consume_byte();
-        }
-        _ => break
-      }
+    while let Some(b'0'..=b'7') = self.peek_byte() {
+      self.consume_byte();
     }
     Token::IntLit(usize::from_str_radix(&self.slice()[2..], 8).unwrap())
   }
@@ -541,13 +507,8 @@ impl<'input> Lexer<'input> {
   }
 
   fn read_decimal_digits(&mut self) {
-    loop {
-      match self.peek_byte() {
-        Some(b'0'..=b'9') => {
-          self.consume_byte();
-        }
-        _ => break
-      }
+    while let Some(b'0'..=b'9') = self.peek_byte() {
+      self.consume_byte();
     }
   }
 
@@ -565,7 +526,6 @@ impl<'input> Lexer<'input> {
 
   #[inline(always)]
   fn consume_byte(&mut self) -> Option<u8> {
-    self.column += 1;
     let byte = self.peek_byte()?;
     self.end += 1;
     Some(byte)
@@ -583,7 +543,7 @@ impl<'input> Lexer<'input> {
   }
 
   #[inline(always)]
-  fn location(&self) -> Location { Location { line: self.line, column: self.column } }
+  fn span(&self) -> Span { Span::new(self.file, self.begin, self.end) }
 
   #[inline(always)]
   fn slice(&self) -> &'input str {
@@ -591,7 +551,7 @@ impl<'input> Lexer<'input> {
   }
 }
 
-fn unescape(loc: Location, s: &str) -> Result<Vec<u8>, Error> {
+fn unescape(span: Span, s: &str) -> Result<Vec<u8>, Error> {
   let mut iterator = s.bytes().peekable();
   let mut buffer = Vec::new();
 
@@ -625,7 +585,7 @@ fn unescape(loc: Location, s: &str) -> Result<Vec<u8>, Error> {
             }
             buffer.push(byte);
           }
-          _ => { return Err(Error::UnknownEscape(loc)) }
+          _ => { return Err(Error::UnknownEscape(span)) }
         }
       }
       Some(byte) => {
@@ -3,20 +3,20 @@
  * SPDX-License-Identifier: GPL-2.0-only
  */
 
-use crate::util::{MRes, RefStr};
-use crate::resolve::{ResolvedDef,resolve_defs};
+use crate::util::{ErrorList, MRes, RefStr};
+use crate::resolve::ResolvedDef;
+use crate::source::{FileId, SourceMap, Span};
 use lexer::Token;
 use lalrpop_util::{self,lalrpop_mod};
-use std::collections::HashMap;
-use std::{error, fs, fmt, io};
+use std::collections::{HashMap, HashSet};
+use std::{error, fmt, io};
 use std::fmt::Formatter;
 use std::hash::Hash;
-use std::os::unix::fs::MetadataExt;
 use std::path::PathBuf;
 
 mod lexer;
 
-lalrpop_mod!(maple, "/parse/maple.rs");
+lalrpop_mod!(#[allow(clippy::all)] maple, "/parse/maple.rs");
 
 /// Syntax tree produced by the parser
 
@@ -32,6 +32,12 @@ impl fmt::Display for IsMut {
   }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IsPub { Yes, No }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IsExport { Yes, No }
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Path(Vec<RefStr>);
 
@@ -53,7 +59,19 @@ impl fmt::Display for Path {
 }
 
 #[derive(Clone, Debug)]
-pub enum Ty {
+pub struct Ty {
+  pub span: Span,
+  pub kind: TyKind
+}
+
+impl Ty {
+  pub fn new(span: Span, kind: TyKind) -> Ty {
+    Ty { span, kind }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub enum TyKind {
   Bool,
   Uint8,
   Int8,
@@ -86,7 +104,23 @@ pub enum BinOp {
 }
 
 #[derive(Clone, Debug)]
-pub enum Expr {
+pub struct Expr {
+  pub span: Span,
+  pub kind: ExprKind
+}
+
+impl Expr {
+  pub fn new(span: Span, kind: ExprKind) -> Expr {
+    Expr { span, kind }
+  }
+
+  fn bin(op: BinOp, lhs: Expr, rhs: Expr) -> Expr {
+    Expr::new(lhs.span.to(rhs.span), ExprKind::Bin(op, Box::new(lhs), Box::new(rhs)))
+  }
+}
+
+#[derive(Clone, Debug)]
+pub enum ExprKind {
   Path(Path),
   Nil,
   Bool(bool),
@@ -114,14 +148,14 @@ pub enum Expr {
   Continue,
   Break(Box<Expr>),
   Return(Box<Expr>),
-  Let(RefStr, IsMut, Option<Ty>, Option<Box<Expr>>),
+  Let(Span, RefStr, IsMut, Option<Ty>, Option<Box<Expr>>),
   If(Box<Expr>, Box<Expr>, Box<Expr>),
   While(Box<Expr>, Box<Expr>),
   Loop(Box<Expr>),
   Match(Box<Expr>, Vec<(Option<RefStr>, RefStr, Expr)>)
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DefId(usize);
 
 impl fmt::Debug for DefId {
@@ -150,6 +184,7 @@ pub struct TypeDef {
 
 #[derive(Clone, Debug)]
 pub struct StructDef {
+  pub span: Span,
   pub name: RefStr,
   pub type_params: Vec<RefStr>,
   pub params: Vec<(RefStr, Ty)>
@@ -157,6 +192,7 @@ pub struct StructDef {
 
 #[derive(Clone, Debug)]
 pub struct UnionDef {
+  pub span: Span,
   pub name: RefStr,
   pub type_params: Vec<RefStr>,
   pub params: Vec<(RefStr, Ty)>
@@ -164,6 +200,7 @@ pub struct UnionDef {
 
 #[derive(Clone, Debug)]
 pub struct EnumDef {
+  pub span: Span,
   pub name: RefStr,
   pub type_params: Vec<RefStr>,
   pub variants: Vec<Variant>
@@ -171,6 +208,7 @@ pub struct EnumDef {
 
 #[derive(Clone, Debug)]
 pub struct VariantDef {
+  #[allow(dead_code)]
   pub name: RefStr,
   pub parent_enum: DefId,
   pub variant_index: usize
@@ -178,8 +216,8 @@ pub struct VariantDef {
 
 #[derive(Clone, Debug)]
 pub enum Variant {
-  Unit(RefStr),
-  Struct(RefStr, Vec<(RefStr, Ty)>),
+  Unit(Span, RefStr),
+  Struct(Span, RefStr, Vec<(RefStr, Ty)>),
 }
 
 #[derive(Clone, Debug)]
@@ -191,7 +229,9 @@ pub struct ConstDef {
 
 #[derive(Clone, Debug)]
 pub struct DataDef {
+  pub span: Span,
   pub name: RefStr,
+  pub is_export: IsExport,
   pub is_mut: IsMut,
   pub ty: Ty,
   pub init: Expr
@@ -199,14 +239,16 @@ pub struct DataDef {
 
 #[derive(Clone, Debug)]
 pub struct FuncDef {
+  pub span: Span,
   pub name: RefStr,
+  pub is_export: IsExport,
   pub type_params: Vec<RefStr>,
   pub params: Vec<ParamDef>,
   pub ret_ty: Ty,
   pub body: Expr
 }
 
-pub type ParamDef = (RefStr, IsMut, Ty);
+pub type ParamDef = (Span, RefStr, IsMut, Ty);
 
 #[derive(Clone, Debug)]
 pub struct ExternDataDef {
@@ -225,34 +267,81 @@ pub struct ExternFuncDef {
 
 /// Parser API
 
-pub fn parse_bundle(path: &std::path::Path) -> MRes<Repository> {
-  let mut repo = Repository::new();
-  repo.parse_module(path)?;
-  resolve_defs(&mut repo)?;
+pub fn parse_bundle(sources: &mut SourceMap, path: &std::path::Path, search_dirs: Vec<PathBuf>) -> MRes<Repository> {
+  let mut repo = Repository::new(search_dirs);
+  repo.parse_module(sources, path, Vec::new())?;
+  let errors = std::mem::take(&mut repo.errors);
+  ErrorList::check(errors
+    .into_iter()
+    .map(|error| Box::new(error) as Box<dyn error::Error + Send + Sync>)
+    .collect())?;
   Ok(repo)
 }
 
+/// Symbol imported from a module with `from`
+
+#[derive(Debug)]
+pub struct FromImport {
+  /// Module the symbol is imported into
+  pub scope_id: DefId,
+  /// Module the symbol is imported from
+  pub module_id: DefId,
+  /// Re-export the symbol
+  pub is_pub: IsPub,
+  pub span: Span,
+  pub name: RefStr,
+  pub alias: RefStr
+}
+
 #[derive(Debug)]
 pub struct Repository {
   def_cnt: usize,
+  // Directories of the modules being parsed, with the path of modules found in them
+  module_dirs: Vec<(PathBuf, Vec<RefStr>)>,
+  // Directories to search after those of the importing modules
   search_dirs: Vec<PathBuf>,
   current_scope: Vec<DefId>,
-  ino_to_module: HashMap<u64, DefId>,
+  // Modules parsed so far, by canonical path
+  path_to_module: HashMap<PathBuf, DefId>,
+  // Path of each module, relative to the root module or the search directory it was found in
+  module_paths: HashMap<DefId, Vec<RefStr>>,
   parent_scope: HashMap<DefId, DefId>,
+  errors: Vec<Error>,
+  /// Imports, along with the module or package they are bound in
+  pub imports: Vec<(DefId, Span, RefStr)>,
+  /// Packages created for the leading crumbs of imported module paths
+  pub packages: HashSet<DefId>,
+  // Symbols bound for the leading crumbs of imported module paths
+  crumb_syms: HashSet<(DefId, RefStr)>,
+  /// Symbols imported with `from`, these are bound by the resolver
+  pub from_imports: Vec<FromImport>,
+  /// Where each symbol was defined
+  sym_spans: HashMap<(DefId, RefStr), Span>,
+  /// Symbols visible outside the module they are defined in
+  pub_syms: HashSet<(DefId, RefStr)>,
   pub parsed_defs: HashMap<DefId, Def>,
   pub resolved_defs: HashMap<DefId, ResolvedDef>,
   pub syms: HashMap<DefId, HashMap<RefStr, DefId>>
 }
 
 impl Repository {
-  pub fn new() -> Repository {
+  pub fn new(search_dirs: Vec<PathBuf>) -> Repository {
     // Crate repository
     Repository {
       def_cnt: 0,
-      search_dirs: vec![ PathBuf::from(env!("MPC_STD_DIR")) ],
+      module_dirs: Vec::new(),
+      search_dirs,
       current_scope: Vec::new(),
-      ino_to_module: HashMap::new(),
+      path_to_module: HashMap::new(),
+      module_paths: HashMap::new(),
       parent_scope: HashMap::new(),
+      errors: Vec::new(),
+      imports: Vec::new(),
+      packages: HashSet::new(),
+      crumb_syms: HashSet::new(),
+      from_imports: Vec::new(),
+      sym_spans: HashMap::new(),
+      pub_syms: HashSet::new(),
       parsed_defs: HashMap::new(),
       resolved_defs: HashMap::new(),
       syms: HashMap::new()
@@ -263,19 +352,30 @@ impl Repository {
     let mut cur_id = scope_id;
     for crumb in path.crumbs().iter() {
       let symtab = self.syms.get(&cur_id).unwrap();
-      if let Some(def_id) = symtab.get(crumb) {
-        cur_id = *def_id;
-      } else {
-        return None
-      }
+      cur_id = *symtab.get(crumb)?;
     }
     Some(cur_id)
   }
 
+  /// Check if a symbol can be referred to from outside its module
+  pub fn is_pub(&self, scope_id: DefId, name: RefStr) -> bool {
+    self.pub_syms.contains(&(scope_id, name))
+  }
+
+  /// Location of the definition a symbol refers to
+  pub fn sym_span(&self, scope_id: DefId, name: RefStr) -> Span {
+    self.sym_spans[&(scope_id, name)]
+  }
+
   pub fn parent(&self, def_id: DefId) -> DefId {
     *self.parent_scope.get(&def_id).unwrap()
   }
 
+  /// Path of the module a definition is in
+  pub fn module_path(&self, def_id: DefId) -> &[RefStr] {
+    &self.module_paths[&self.parent(def_id)]
+  }
+
   pub fn parsed_by_id(&self, def_id: DefId) -> &Def {
     self.parsed_defs.get(&def_id).unwrap()
   }
@@ -294,93 +394,218 @@ impl Repository {
     id
   }
 
-  fn sym(&mut self, location: Location, name: RefStr, def: DefId) -> Result<(), Error> {
+  fn sym(&mut self, is_pub: IsPub, span: Span, name: RefStr, def: DefId) {
+    self.sym_in(*self.current_scope.last().unwrap(), is_pub, span, name, def)
+  }
+
+  fn sym_in(&mut self, scope_id: DefId, is_pub: IsPub, span: Span, name: RefStr, def: DefId) {
+    if let Err(error) = self.bind(scope_id, is_pub, span, name, def) {
+      self.errors.push(error)
+    }
+  }
+
+  /// Add a symbol to the symbol table of a module or package
+  pub fn bind(&mut self, scope_id: DefId, is_pub: IsPub, span: Span, name: RefStr, def: DefId) -> Result<(), Error> {
     let scope = self.syms
-      .entry(*self.current_scope.last().unwrap())
-      .or_insert_with(|| HashMap::new());
+      .entry(scope_id)
+      .or_default();
 
     match scope.insert(name, def) {
-      None => Ok(()),         // No redefinition
+      None => {               // No redefinition
+        self.sym_spans.insert((scope_id, name), span);
+        if is_pub == IsPub::Yes {
+          self.pub_syms.insert((scope_id, name));
+        }
+        Ok(())
+      }
       Some(..) => {           // Redefinition errors
-        Err(Error::Redefinition(location, name))
+        // Symbols imported with from are bound after the module is parsed,
+        // so the one bound first might come later in the source
+        let previous = self.sym_spans[&(scope_id, name)];
+        match previous.file == span.file && previous.lo > span.lo {
+          true => Err(Error::Redefinition(previous, name, span)),
+          false => Err(Error::Redefinition(span, name, previous))
+        }
       }
     }
   }
 
-  fn find_module(&mut self, location: Location, name: RefStr) -> Result<PathBuf, Error> {
-    for dir in self.search_dirs.iter().rev() {
-      let path = dir
-        .join(std::path::Path::new(name.borrow_rs()))
-        .with_extension("m");
-      if path.is_file() { return Ok(path) }
+  /// Bind an imported module to its path, binding the leading crumbs to the
+  /// modules in their directory's mod.m, or to packages if there is none
+  fn import(&mut self, sources: &mut SourceMap, is_pub: IsPub, span: Span, path: &Path, module_id: DefId) {
+    let (name, packages) = path.crumbs().split_last().unwrap();
+
+    // Only the outermost name decides visibility, everything inside packages is public
+    let mut scope_id = *self.current_scope.last().unwrap();
+    let mut crumb_is_pub = is_pub;
+    for (index, crumb) in packages.iter().enumerate() {
+      let dir_module = self.find_dir_module(sources, span, &path.crumbs()[..=index]);
+      let existing = self.syms
+        .get(&scope_id)
+        .and_then(|symtab| symtab.get(crumb))
+        .filter(|def_id| self.packages.contains(def_id) || Some(**def_id) == dir_module)
+        .cloned();
+      let package_id = match existing {
+        Some(package_id) => package_id,
+        None => {
+          let package_id = dir_module.unwrap_or_else(|| {
+            let package_id = self.new_id();
+            self.packages.insert(package_id);
+            package_id
+          });
+          self.sym_in(scope_id, crumb_is_pub, span, *crumb, package_id);
+          self.crumb_syms.insert((scope_id, *crumb));
+          package_id
+        }
+      };
+      // Packages are shared between imports into the same module
+      if crumb_is_pub == IsPub::Yes {
+        self.pub_syms.insert((scope_id, *crumb));
+      }
+      scope_id = package_id;
+      crumb_is_pub = IsPub::Yes;
+    }
+
+    // A module already bound for the leading crumb of another import, or
+    // imported by the module it is nested in, is not a redefinition
+    let existing = self.syms
+      .get(&scope_id)
+      .and_then(|symtab| symtab.get(name))
+      .cloned();
+    let is_current = scope_id == *self.current_scope.last().unwrap();
+    if existing == Some(module_id) && (self.crumb_syms.remove(&(scope_id, *name)) || !is_current) {
+      if is_current {
+        self.sym_spans.insert((scope_id, *name), span);
+      }
+      if crumb_is_pub == IsPub::Yes {
+        self.pub_syms.insert((scope_id, *name));
+      }
+    } else {
+      self.sym_in(scope_id, crumb_is_pub, span, *name, module_id);
+    }
+    // Re-exports are never unused
+    if is_pub == IsPub::No {
+      self.imports.push((scope_id, span, *name));
+    }
+  }
+
+  /// Module in the mod.m file of the directory named by crumbs, if there is one
+  fn find_dir_module(&mut self, sources: &mut SourceMap, span: Span, crumbs: &[RefStr]) -> Option<DefId> {
+    let (file, module_path) = self.find_module(sources, span, &Path(crumbs.to_vec())).ok()?;
+    if file.file_name()? != "mod.m" {
+      return None
+    }
+    match self.parse_module(sources, &file, module_path) {
+      Ok(module_id) => Some(module_id),
+      Err(error) => {
+        self.errors.push(error);
+        None
+      }
     }
-    Err(Error::UnknownModule(location, name))
   }
 
-  fn parse_module(&mut self, path: &std::path::Path) -> Result<DefId, Error> {
-    // Return previous copy if we've parsed a module with the same inode number
-    let ino = fs::metadata(path)
-      .map_err(|error| Error::IoError(path.to_path_buf(), error))?
-      .ino();
-    if let Some(def_id) = self.ino_to_module.get(&ino) {
+  /// Bind an imported module to a name of choice
+  fn import_as(&mut self, is_pub: IsPub, span: Span, name: RefStr, module_id: DefId) {
+    let scope_id = *self.current_scope.last().unwrap();
+    self.sym_in(scope_id, is_pub, span, name, module_id);
+    if is_pub == IsPub::No {
+      self.imports.push((scope_id, span, name));
+    }
+  }
+
+  /// Queue a symbol of a module to be imported under a name of choice
+  fn import_from(&mut self, is_pub: IsPub, span: Span, module_id: DefId, name: RefStr, alias: RefStr) {
+    let scope_id = *self.current_scope.last().unwrap();
+    self.from_imports.push(FromImport { scope_id, module_id, is_pub, span, name, alias });
+    if is_pub == IsPub::No {
+      self.imports.push((scope_id, span, alias));
+    }
+  }
+
+  fn find_module(&mut self, sources: &SourceMap, span: Span, path: &Path) -> Result<(PathBuf, Vec<RefStr>), Error> {
+    // a::b::c is either a/b/c.m, or a/b/c/mod.m
+    let relative: PathBuf = path.crumbs().iter().map(|crumb| crumb.borrow_rs()).collect();
+    let candidates = [ relative.with_extension("m"), relative.join("mod.m") ];
+
+    // The innermost importing module comes first, then the configured directories
+    let mut tried = Vec::new();
+    let dirs = self.module_dirs
+      .iter()
+      .rev()
+      .map(|(dir, prefix)| (dir, prefix.as_slice()))
+      .chain(self.search_dirs.iter().map(|dir| (dir, &[][..])));
+    for (dir, prefix) in dirs {
+      for candidate in candidates.iter() {
+        let file = dir.join(candidate);
+        if sources.provider().is_file(&file) {
+          return Ok((file, prefix.iter().chain(path.crumbs().iter()).cloned().collect()))
+        }
+        tried.push(file);
+      }
+    }
+    Err(Error::UnknownModule(span, path.clone(), tried))
+  }
+
+  fn parse_module(&mut self, sources: &mut SourceMap, path: &std::path::Path, module_path: Vec<RefStr>) -> Result<DefId, Error> {
+    // Return previous copy if we've parsed the same file through another path
+    let canonical = sources.provider().canonicalize(path)
+      .map_err(|error| Error::IoError(path.to_path_buf(), error))?;
+    if let Some(def_id) = self.path_to_module.get(&canonical) {
       return Ok(*def_id)
     }
     // Otherwise we can go ahead and parse it
-    let input = fs::read_to_string(path)
+    let input = sources.provider().read(path)
       .map_err(|error| Error::IoError(path.to_path_buf(), error))?;
-    let lexer = lexer::Lexer::new(&input);
+    let file = sources.add(path, input.clone());
+    let lexer = lexer::Lexer::new(file, &input);
     let parser = maple::ModuleParser::new();
     let module_id = self.new_id();
-    self.ino_to_module.insert(ino, module_id);
-    self.search_dirs.push(path.parent().unwrap().to_path_buf());
-    self.current_scope.push(module_id);
-    let result = match parser.parse(self, lexer) {
-      Ok(()) => Ok(module_id),
-      Err(err) => Err(Error::from_lalrpop(err))
+    self.path_to_module.insert(canonical, module_id);
+    // Modules next to this one are named relative to its directory
+    let prefix = match path.file_name() {
+      Some(name) if name == "mod.m" => module_path.clone(),
+      _ => module_path.split_last().map(|(_, prefix)| prefix.to_vec()).unwrap_or_default()
     };
+    self.module_paths.insert(module_id, module_path);
+    self.module_dirs.push((path.parent().unwrap().to_path_buf(), prefix));
+    self.current_scope.push(module_id);
+    // Syntax errors are collected during parsing, anything that
+    // makes it here could not be recovered from
+    if let Err(err) = parser.parse(self, sources, file, lexer) {
+      self.errors.push(Error::from_lalrpop(file, err));
+    }
     self.current_scope.pop();
-    self.search_dirs.pop();
-    result
-  }
-}
-
-#[derive(Clone,Copy,Default,Debug)]
-pub struct Location {
-  pub line: usize,
-  pub column: usize
-}
-
-impl fmt::Display for Location {
-  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-    write!(fmt, "line {} column {}", self.line, self.column)
+    self.module_dirs.pop();
+    Ok(module_id)
   }
 }
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
   IoError(PathBuf, io::Error),
-  UnknownToken(Location),
-  UnknownEscape(Location),
-  UnterminatedStr(Location),
-  UnterminatedChar(Location),
-  UnterminatedComment(Location),
-  InvalidChar(Location),
-  UnexpectedToken(Location),
-  UnexpectedEndOfFile(Location),
-  UnknownModule(Location, RefStr),
-  Redefinition(Location, RefStr)
+  UnknownToken(Span),
+  UnknownEscape(Span),
+  UnterminatedStr(Span),
+  UnterminatedChar(Span),
+  UnterminatedComment(Span),
+  InvalidChar(Span),
+  UnexpectedToken(Span, Vec<String>),
+  UnexpectedEndOfFile(Span, Vec<String>),
+  UnknownModule(Span, Path, Vec<PathBuf>),
+  Redefinition(Span, RefStr, Span)
 }
 
 impl Error {
-  fn from_lalrpop(err: lalrpop_util::ParseError<Location, Token, Error>) -> Error {
+  fn from_lalrpop(file: FileId, err: lalrpop_util::ParseError<usize, Token, Error>) -> Error {
     match err {
       // Parser expected a different token
-      lalrpop_util::ParseError::UnrecognizedToken { token: (location, ..), .. } => {
-        Error::UnexpectedToken(location)
+      lalrpop_util::ParseError::UnrecognizedToken { token: (lo, _, hi), expected } => {
+        Error::UnexpectedToken(Span::new(file, lo, hi), expected)
       }
       // Parser expected token instead of EOF
-      lalrpop_util::ParseError::UnrecognizedEOF { location, .. } => {
-        Error::UnexpectedEndOfFile(location)
+      lalrpop_util::ParseError::UnrecognizedEOF { location, expected } => {
+        Error::UnexpectedEndOfFile(Span::new(file, location, location), expected)
       }
       // Lexer errors propagate to here
       lalrpop_util::ParseError::User { error } => {
@@ -393,20 +618,54 @@ impl Error {
   }
 }
 
+impl Error {
+  pub fn span(&self) -> Option<Span> {
+    match self {
+      Error::IoError(..) => None,
+      Error::UnknownToken(span) |
+      Error::UnknownEscape(span) |
+      Error::UnterminatedStr(span) |
+      Error::UnterminatedChar(span) |
+      Error::UnterminatedComment(span) |
+      Error::InvalidChar(span) |
+      Error::UnexpectedToken(span, ..) |
+      Error::UnexpectedEndOfFile(span, ..) |
+      Error::UnknownModule(span, ..) |
+      Error::Redefinition(span, ..) => Some(*span)
+    }
+  }
+
+  pub fn code(&self) -> &'static str {
+    match self {
+      Error::IoError(..) => "E0001",
+      Error::UnknownToken(..) => "E0002",
+      Error::UnknownEscape(..) => "E0003",
+      Error::UnterminatedStr(..) => "E0004",
+      Error::UnterminatedChar(..) => "E0005",
+      Error::UnterminatedComment(..) => "E0006",
+      Error::InvalidChar(..) => "E0007",
+      Error::UnexpectedToken(..) => "E0008",
+      Error::UnexpectedEndOfFile(..) => "E0009",
+      Error::UnknownModule(..) => "E0010",
+      Error::Redefinition(..) => "E0011"
+    }
+  }
+}
+
 impl fmt::Display for Error {
   fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
     match self {
       Error::IoError(path, error) => write!(fmt, "{}: {}", path.to_string_lossy(), error),
-      Error::UnknownToken(location) => write!(fmt, "Error at {}: Unknown token", location),
-      Error::UnknownEscape(location) => write!(fmt, "Error at {}: Unknown escape sequence", location),
-      Error::UnterminatedStr(location) => write!(fmt, "Error at {}: Unterminated string literal", location),
-      Error::UnterminatedChar(location) => write!(fmt, "Error at {}: Unterminated character literal", location),
-      Error::UnterminatedComment(location) => write!(fmt, "Error at {}: Unterminated block comment", location),
-      Error::InvalidChar(location) => write!(fmt, "Error at {}: Invalid char literal", location),
-      Error::UnexpectedToken(location) => write!(fmt, "Error at {}: Unexpected token", location),
-      Error::UnexpectedEndOfFile(location) => write!(fmt, "Error at {}: Unexpected end of file", location),
-      Error::UnknownModule(location, name) => write!(fmt, "Error at {}: Unknown module {}", location, name),
-      Error::Redefinition(location, name) => write!(fmt, "Error at {}: Re-definition of {}", location, name)
+      Error::UnknownToken(..) => write!(fmt, "Unknown token"),
+      Error::UnknownEscape(..) => write!(fmt, "Unknown escape sequence"),
+      Error::UnterminatedStr(..) => write!(fmt, "Unterminated string literal"),
+      Error::UnterminatedChar(..) => write!(fmt, "Unterminated character literal"),
+      Error::UnterminatedComment(..) => write!(fmt, "Unterminated block comment"),
+      Error::InvalidChar(..) => write!(fmt, "Invalid char literal"),
+      Error::UnexpectedToken(..) => write!(fmt, "Unexpected token"),
+      Error::UnexpectedEndOfFile(..) => write!(fmt, "Unexpected end of file"),
+      Error::UnknownModule(_, name, _) => write!(fmt, "Unknown module {}", name),
+      Error::Redefinition(_, name, _) => write!(fmt, "Re-definition of {}", name)
     }
   }
 }
@@ -4,120 +4,225 @@
  */
 
 use crate::util::*;
-use crate::parse::{self, Repository, DefId, UnOp, BinOp, IsMut};
-use std::collections::HashMap;
+use crate::parse::{self, Repository, DefId, UnOp, BinOp, IsExport, IsMut};
+use crate::lint::{Lint, Warning};
+use crate::source::Span;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Debug, Formatter};
 
-pub fn resolve_defs(repo: &mut Repository) -> MRes<()> {
-  for (def_id, def) in repo.parsed_defs.iter() {
-    match def {
-      parse::Def::Type(def) => {
-        let mut ctx = ResolveCtx::new(repo, repo.parent(*def_id));
-        repo.resolved_defs.insert(*def_id,
-                                  ResolvedDef::Type(ResolvedTypeDef {
-                                    name: def.name,
-                                    ty: ctx.resolve_ty(&def.ty)?,
-                                  }),
-        );
-      }
-      parse::Def::Struct(def) => {
-        let mut ctx = ResolveCtx::new_generic(repo, repo.parent(*def_id), &def.type_params);
-        repo.resolved_defs.insert(*def_id,
-                                  ResolvedDef::Struct(ResolvedStructDef {
-                                    name: def.name,
-                                    type_params: def.type_params.len(),
-                                    params: ctx.resolve_params(&def.params)?,
-                                  }));
-      }
-      parse::Def::Union(def) => {
-        let mut ctx = ResolveCtx::new_generic(repo, repo.parent(*def_id), &def.type_params);
-        repo.resolved_defs.insert(*def_id,
-                                  ResolvedDef::Union(ResolvedUnionDef {
-                                    name: def.name,
-                                    type_params: def.type_params.len(),
-                                    params: ctx.resolve_params(&def.params)?,
-                                  }));
-      }
-      parse::Def::Enum(def) => {
-        let mut ctx = ResolveCtx::new_generic(repo, repo.parent(*def_id), &def.type_params);
-        repo.resolved_defs.insert(*def_id,
-                                  ResolvedDef::Enum(ResolvedEnumDef {
-                                    name: def.name,
-                                    type_params: def.type_params.len(),
-                                    variants: def.variants
-                                      .iter()
-                                      .map(|variant| Ok(match variant {
-                                        parse::Variant::Unit(name) => ResolvedVariant::Unit(*name),
-                                        parse::Variant::Struct(name, params) => ResolvedVariant::Struct(*name, ctx.resolve_params(params)?)
-                                      }))
-                                      .monadic_collect()?,
-                                  }));
-      }
-      parse::Def::Variant(..) => {
-        // NOTE: this does not exist in resolved form
-      }
-      parse::Def::Const(def) => {
-        let mut ctx = ResolveCtx::new(repo, repo.parent(*def_id));
-        repo.resolved_defs.insert(*def_id,
-                                  ResolvedDef::Const(ResolvedConstDef {
-                                    name: def.name,
-                                    ty: ctx.resolve_ty(&def.ty)?,
-                                    val: ctx.resolve_expr(&def.val)?,
-                                  }));
-      }
-      parse::Def::Data(def) => {
-        let mut ctx = ResolveCtx::new(repo, repo.parent(*def_id));
-        repo.resolved_defs.insert(*def_id,
-                                  ResolvedDef::Data(ResolvedDataDef {
-                                    name: def.name,
-                                    is_mut: def.is_mut,
-                                    ty: ctx.resolve_ty(&def.ty)?,
-                                    init: ctx.resolve_expr(&def.init)?,
-                                  }));
-      }
-      parse::Def::Func(def) => {
-        let mut ctx = ResolveCtx::new_func(repo, repo.parent(*def_id),
-                                           &def.type_params, &def.params);
-        repo.resolved_defs.insert(*def_id,
-                                  ResolvedDef::Func(ResolvedFuncDef {
-                                    name: def.name,
-                                    type_params: def.type_params.len(),
-                                    params: def.params
-                                      .iter()
-                                      .map(|(name, is_mut, ty)|
-                                        Ok((*name, *is_mut, ctx.resolve_ty(ty)?)))
-                                      .monadic_collect()?,
-                                    ret_ty: ctx.resolve_ty(&def.ret_ty)?,
-                                    body: ctx.resolve_expr(&def.body)?,
-                                    locals: ctx.locals,
-                                  }));
-      }
-      parse::Def::ExternData(def) => {
-        let mut ctx = ResolveCtx::new(repo, repo.parent(*def_id));
-        repo.resolved_defs.insert(*def_id,
-                                  ResolvedDef::ExternData(ResolvedExternDataDef {
-                                    name: def.name,
-                                    is_mut: def.is_mut,
-                                    ty: ctx.resolve_ty(&def.ty)?,
-                                  }));
-      }
-      parse::Def::ExternFunc(def) => {
-        let mut ctx = ResolveCtx::new(repo, repo.parent(*def_id));
-        repo.resolved_defs.insert(*def_id,
-                                  ResolvedDef::ExternFunc(ResolvedExternFuncDef {
-                                    name: def.name,
-                                    varargs: def.varargs,
-                                    params: ctx.resolve_params(&def.params)?,
-                                    ret_ty: ctx.resolve_ty(&def.ret_ty)?,
-                                  }));
+pub fn resolve_defs(repo: &mut Repository, warnings: &mut Vec<Warning>) -> MRes<()> {
+  // Resolve definitions in a fixed order, so errors are reported in order too
+  let mut def_ids: Vec<DefId> = repo.parsed_defs.keys().cloned().collect();
+  def_ids.sort();
+
+  let mut lint = LintCtx { warnings: Vec::new(), used_syms: HashSet::new() };
+  let mut errors = Vec::new();
+  bind_from_imports(repo, &mut lint, &mut errors);
+
+  for def_id in def_ids.into_iter() {
+    match resolve_def(repo, &mut lint, def_id) {
+      Ok(Some(resolved_def)) => {
+        repo.resolved_defs.insert(def_id, resolved_def);
       }
+      Ok(None) => (),
+      Err(error) => errors.push(error)
     }
   }
-  Ok(())
+
+  // Uses are only known if everything resolved
+  if errors.is_empty() {
+    for (scope_id, span, name) in repo.imports.iter() {
+      if !lint.used_syms.contains(&(*scope_id, *name)) {
+        lint.warnings.push(Warning::new(Lint::UnusedImports, *span, format!("Unused import {}", name)));
+      }
+    }
+  }
+
+  warnings.extend(lint.warnings);
+  ErrorList::check(errors)
+}
+
+/// Add symbols imported with `from` to the symbol tables of the importing modules
+fn bind_from_imports(repo: &mut Repository, lint: &mut LintCtx, errors: &mut Vec<Box<dyn std::error::Error + Send + Sync>>) {
+  let mut pending = std::mem::take(&mut repo.from_imports);
+
+  // Modules can import symbols that were imported with `from` themselves,
+  // so keep binding until no more progress is made
+  loop {
+    let count = pending.len();
+    let mut remaining = Vec::new();
+    for import in pending.into_iter() {
+      let def_id = repo.syms
+        .get(&import.module_id)
+        .and_then(|symtab| symtab.get(&import.name))
+        .cloned();
+      match def_id {
+        Some(..) if !repo.is_pub(import.module_id, import.name) => {
+          let def_span = repo.sym_span(import.module_id, import.name);
+          errors.push(Box::new(ResolveError::PrivateDefinition(import.span, import.name, def_span)));
+        }
+        Some(def_id) => {
+          lint.used_syms.insert((import.module_id, import.name));
+          if let Err(error) = repo.bind(import.scope_id, import.is_pub, import.span, import.alias, def_id) {
+            errors.push(Box::new(error));
+          }
+        }
+        None => remaining.push(import)
+      }
+    }
+    pending = remaining;
+    if pending.is_empty() || pending.len() == count {
+      break;
+    }
+  }
+
+  // Anything left does not exist
+  for import in pending.into_iter() {
+    let candidates = repo.syms
+      .get(&import.module_id)
+      .into_iter()
+      .flat_map(|symtab| symtab.keys().cloned());
+    let suggestion = closest_match(import.name, candidates);
+    errors.push(Box::new(ResolveError::UnresolvedImport(import.span, import.name, suggestion)));
+  }
+}
+
+/// Lint state shared between definitions
+struct LintCtx {
+  warnings: Vec<Warning>,
+  // Global symbols referred to, by the scope they were found in
+  used_syms: HashSet<(DefId, RefStr)>
+}
+
+fn resolve_def(repo: &Repository, lint: &mut LintCtx, def_id: DefId) -> MRes<Option<ResolvedDef>> {
+  Ok(match repo.parsed_by_id(def_id) {
+    parse::Def::Type(def) => {
+      let mut ctx = ResolveCtx::new(repo, lint, repo.parent(def_id));
+      Some(ResolvedDef::Type(ResolvedTypeDef {
+        name: def.name,
+        ty: ctx.resolve_ty(&def.ty)?,
+      }))
+    }
+    parse::Def::Struct(def) => {
+      let mut ctx = ResolveCtx::new_generic(repo, lint, repo.parent(def_id), &def.type_params);
+      Some(ResolvedDef::Struct(ResolvedStructDef {
+        span: def.span,
+        name: def.name,
+        type_params: def.type_params.len(),
+        params: ctx.resolve_params(&def.params)?,
+      }))
+    }
+    parse::Def::Union(def) => {
+      let mut ctx = ResolveCtx::new_generic(repo, lint, repo.parent(def_id), &def.type_params);
+      Some(ResolvedDef::Union(ResolvedUnionDef {
+        span: def.span,
+        name: def.name,
+        type_params: def.type_params.len(),
+        params: ctx.resolve_params(&def.params)?,
+      }))
+    }
+    parse::Def::Enum(def) => {
+      let mut ctx = ResolveCtx::new_generic(repo, lint, repo.parent(def_id), &def.type_params);
+      Some(ResolvedDef::Enum(ResolvedEnumDef {
+        span: def.span,
+        name: def.name,
+        type_params: def.type_params.len(),
+        variants: def.variants
+          .iter()
+          .map(|variant| Ok(match variant {
+            parse::Variant::Unit(_, name) => ResolvedVariant::Unit(*name),
+            parse::Variant::Struct(_, name, params) => ResolvedVariant::Struct(*name, ctx.resolve_params(params)?)
+          }))
+          .monadic_collect()?,
+      }))
+    }
+    parse::Def::Variant(..) => {
+      // NOTE: this does not exist in resolved form
+      None
+    }
+    parse::Def::Const(def) => {
+      let mut ctx = ResolveCtx::new(repo, lint, repo.parent(def_id));
+      Some(ResolvedDef::Const(ResolvedConstDef {
+        name: def.name,
+        ty: ctx.resolve_ty(&def.ty)?,
+        val: ctx.resolve_expr(&def.val)?,
+      }))
+    }
+    parse::Def::Data(def) => {
+      let mut ctx = ResolveCtx::new(repo, lint, repo.parent(def_id));
+      Some(ResolvedDef::Data(ResolvedDataDef {
+        span: def.span,
+        name: def.name,
+        is_export: def.is_export,
+        is_mut: def.is_mut,
+        ty: ctx.resolve_ty(&def.ty)?,
+        init: ctx.resolve_expr(&def.init)?,
+      }))
+    }
+    parse::Def::Func(def) => {
+      // Exported functions keep their name, so there can only be one instance
+      if def.is_export == IsExport::Yes && !def.type_params.is_empty() {
+        return Err(Box::new(ResolveError::GenericExport(def.span, def.name)));
+      }
+      let mut ctx = ResolveCtx::new_func(repo, lint, repo.parent(def_id),
+               &def.type_params, &def.params);
+      let params = def.params
+        .iter()
+        .map(|(span, name, is_mut, ty)|
+          Ok((*span, *name, *is_mut, ctx.resolve_ty(ty)?)))
+        .monadic_collect()?;
+      let ret_ty = ctx.resolve_ty(&def.ret_ty)?;
+      let body = ctx.resolve_expr(&def.body)?;
+      // NOTE: uses inside expressions that failed to resolve are not known
+      ErrorList::check(std::mem::take(&mut ctx.errors)
+        .into_iter()
+        .map(|error| Box::new(error) as Box<dyn std::error::Error + Send + Sync>)
+        .collect())?;
+      ctx.check_unused();
+      Some(ResolvedDef::Func(ResolvedFuncDef {
+        span: def.span,
+        name: def.name,
+        is_export: def.is_export,
+        type_params: def.type_params.len(),
+        params,
+        ret_ty,
+        body,
+        locals: ctx.local_names
+          .into_iter()
+          .zip(ctx.locals)
+          .map(|((span, name, _), (is_mut, ty))| (span, name, is_mut, ty))
+          .collect(),
+        bindings: ctx.bindings,
+      }))
+    }
+    parse::Def::ExternData(def) => {
+      let mut ctx = ResolveCtx::new(repo, lint, repo.parent(def_id));
+      Some(ResolvedDef::ExternData(ResolvedExternDataDef {
+        name: def.name,
+        is_mut: def.is_mut,
+        ty: ctx.resolve_ty(&def.ty)?,
+      }))
+    }
+    parse::Def::ExternFunc(def) => {
+      let mut ctx = ResolveCtx::new(repo, lint, repo.parent(def_id));
+      Some(ResolvedDef::ExternFunc(ResolvedExternFuncDef {
+        name: def.name,
+        varargs: def.varargs,
+        params: ctx.resolve_params(&def.params)?,
+        ret_ty: ctx.resolve_ty(&def.ret_ty)?,
+      }))
+    }
+  })
+}
+
+#[derive(Debug)]
+pub struct ResolvedTy {
+  pub span: Span,
+  pub kind: ResolvedTyKind
 }
 
 #[derive(Debug)]
-pub enum ResolvedTy {
+pub enum ResolvedTyKind {
   Bool,
   Uint8,
   Int8,
@@ -145,7 +250,19 @@ pub enum ResolvedTy {
 
 
 #[derive(Debug)]
-pub enum ResolvedExpr {
+pub struct ResolvedExpr {
+  pub span: Span,
+  pub kind: ResolvedExprKind
+}
+
+impl ResolvedExpr {
+  fn new(span: Span, kind: ResolvedExprKind) -> ResolvedExpr {
+    ResolvedExpr { span, kind }
+  }
+}
+
+#[derive(Debug)]
+pub enum ResolvedExprKind {
   // Literals
   Nil,
   Bool(bool),
@@ -193,7 +310,10 @@ pub enum ResolvedExpr {
   If(Box<ResolvedExpr>, Box<ResolvedExpr>, Box<ResolvedExpr>),
   While(Box<ResolvedExpr>, Box<ResolvedExpr>),
   Loop(Box<ResolvedExpr>),
-  Match(Box<ResolvedExpr>, Vec<(Option<usize>, RefStr, ResolvedExpr)>)
+  Match(Box<ResolvedExpr>, Vec<(Option<usize>, RefStr, ResolvedExpr)>),
+
+  // Placeholder for an expression that failed to resolve
+  Error
 }
 
 #[derive(Debug)]
@@ -249,12 +369,14 @@ impl ResolvedDef {
 
 #[derive(Debug)]
 pub struct ResolvedTypeDef {
+  #[allow(dead_code)]
   pub name: RefStr,
   pub ty: ResolvedTy,
 }
 
 #[derive(Debug)]
 pub struct ResolvedStructDef {
+  pub span: Span,
   pub name: RefStr,
   pub type_params: usize,
   pub params: Vec<(RefStr, ResolvedTy)>,
@@ -262,6 +384,7 @@ pub struct ResolvedStructDef {
 
 #[derive(Debug)]
 pub struct ResolvedUnionDef {
+  pub span: Span,
   pub name: RefStr,
   pub type_params: usize,
   pub params: Vec<(RefStr, ResolvedTy)>,
@@ -269,6 +392,7 @@ pub struct ResolvedUnionDef {
 
 #[derive(Debug)]
 pub struct ResolvedEnumDef {
+  pub span: Span,
   pub name: RefStr,
   pub type_params: usize,
   pub variants: Vec<ResolvedVariant>,
@@ -282,14 +406,18 @@ pub enum ResolvedVariant {
 
 #[derive(Debug)]
 pub struct ResolvedConstDef {
+  #[allow(dead_code)]
   pub name: RefStr,
+  #[allow(dead_code)]
   pub ty: ResolvedTy,
   pub val: ResolvedExpr,
 }
 
 #[derive(Debug)]
 pub struct ResolvedDataDef {
+  pub span: Span,
   pub name: RefStr,
+  pub is_export: IsExport,
   pub is_mut: IsMut,
   pub ty: ResolvedTy,
   pub init: ResolvedExpr,
@@ -297,11 +425,14 @@ pub struct ResolvedDataDef {
 
 #[derive(Debug)]
 pub struct ResolvedFuncDef {
+  pub span: Span,
   pub name: RefStr,
+  pub is_export: IsExport,
   pub type_params: usize,
-  pub params: Vec<(RefStr, IsMut, ResolvedTy)>,
+  pub params: Vec<(Span, RefStr, IsMut, ResolvedTy)>,
   pub ret_ty: ResolvedTy,
-  pub locals: Vec<(IsMut, Option<ResolvedTy>)>,
+  pub locals: Vec<(Span, RefStr, IsMut, Option<ResolvedTy>)>,
+  pub bindings: Vec<(Span, RefStr)>,
   pub body: ResolvedExpr,
 }
 
@@ -323,22 +454,33 @@ pub struct ResolvedExternFuncDef {
 struct ResolveCtx<'a> {
   repo: &'a Repository,
 
+  // Lint state
+  lint: &'a mut LintCtx,
+
   // Parent scope
   parent_id: DefId,
 
   // Local variables
   locals: Vec<(IsMut, Option<ResolvedTy>)>,
 
-  // Match bindings
-  bindings: usize,
+  // Names of parameters and local variables, and if they were used
+  param_names: Vec<(Span, RefStr, bool)>,
+  local_names: Vec<(Span, RefStr, bool)>,
+
+  // Match bindings, with the span of the case they are bound in
+  bindings: Vec<(Span, RefStr)>,
 
   // Symbol table
   scopes: Vec<HashMap<RefStr, Sym>>,
+
+  // Errors recovered from inside blocks
+  errors: Vec<ResolveError>,
 }
 
 #[derive(Clone)]
 enum Sym {
   Def(DefId),
+  Module,
   Param(usize),
   Local(usize),
   Binding(usize),
@@ -346,14 +488,25 @@ enum Sym {
 }
 
 impl<'a> ResolveCtx<'a> {
-  fn new(repo: &'a Repository, parent_id: DefId) -> Self {
-    ResolveCtx { repo, parent_id, locals: Vec::new(), bindings: 0, scopes: Vec::new() }
+  fn new(repo: &'a Repository, lint: &'a mut LintCtx, parent_id: DefId) -> Self {
+    ResolveCtx {
+      repo,
+      lint,
+      parent_id,
+      locals: Vec::new(),
+      param_names: Vec::new(),
+      local_names: Vec::new(),
+      bindings: Vec::new(),
+      scopes: Vec::new(),
+      errors: Vec::new()
+    }
   }
 
   fn new_generic(repo: &'a Repository,
+                 lint: &'a mut LintCtx,
                  parent_id: DefId,
-                 type_params: &Vec<RefStr>) -> Self {
-    let mut ctx = ResolveCtx::new(repo, parent_id);
+                 type_params: &[RefStr]) -> Self {
+    let mut ctx = ResolveCtx::new(repo, lint, parent_id);
     ctx.newscope();
     for (index, name) in type_params.iter().enumerate() {
       ctx.define(*name, Sym::TParam(index));
@@ -362,16 +515,18 @@ impl<'a> ResolveCtx<'a> {
   }
 
   fn new_func(repo: &'a Repository,
+              lint: &'a mut LintCtx,
               parent_id: DefId,
-              type_params: &Vec<RefStr>,
-              params: &Vec<parse::ParamDef>) -> Self {
-    let mut ctx = ResolveCtx::new(repo, parent_id);
+              type_params: &[RefStr],
+              params: &[parse::ParamDef]) -> Self {
+    let mut ctx = ResolveCtx::new(repo, lint, parent_id);
     ctx.newscope();
     for (index, name) in type_params.iter().enumerate() {
       ctx.define(*name, Sym::TParam(index));
     }
-    for (index, (name, _, _)) in params.iter().enumerate() {
+    for (index, (span, name, _, _)) in params.iter().enumerate() {
       ctx.define(*name, Sym::Param(index));
+      ctx.param_names.push((*span, *name, false));
     }
     ctx
   }
@@ -392,7 +547,7 @@ impl<'a> ResolveCtx<'a> {
   }
 
   /// Resolve symbol by name
-  fn lookup(&self, path: &parse::Path) -> Result<Sym, ResolveError> {
+  fn lookup(&mut self, span: Span, path: &parse::Path) -> Result<Sym, ResolveError> {
     // Single crumb paths can refer to locals
     if path.crumbs().len() == 1 {
       for scope in self.scopes.iter().rev() {
@@ -404,28 +559,78 @@ impl<'a> ResolveCtx<'a> {
 
     // Otherwise check the global symbol table
     if let Some(def_id) = self.repo.locate(self.parent_id, path) {
+      // Record every symbol on the way, they might be imports,
+      // and make sure the ones outside this module are public
+      let mut cur_id = self.parent_id;
+      for crumb in path.crumbs().iter() {
+        if cur_id != self.parent_id && !self.repo.is_pub(cur_id, *crumb) {
+          let def_span = self.repo.sym_span(cur_id, *crumb);
+          return Err(ResolveError::PrivateDefinition(span, *crumb, def_span));
+        }
+        self.lint.used_syms.insert((cur_id, *crumb));
+        cur_id = self.repo.syms[&cur_id][crumb];
+      }
+      // Modules and packages are only namespaces
+      if !self.repo.parsed_defs.contains_key(&def_id) {
+        return Ok(Sym::Module);
+      }
       return Ok(Sym::Def(def_id));
     }
 
-    Err(ResolveError::UnresolvedPath(path.clone()))
+    Err(ResolveError::UnresolvedPath(span, path.clone(), self.suggest(path)))
+  }
+
+  /// Find a name similar to the crumb of a path that failed to resolve
+  fn suggest(&self, path: &parse::Path) -> Option<RefStr> {
+    let crumbs = path.crumbs();
+    let mut candidates = Vec::new();
+
+    // Single crumb paths can refer to locals
+    if crumbs.len() == 1 {
+      for scope in self.scopes.iter() {
+        candidates.extend(scope.keys().cloned());
+      }
+    }
+
+    // Follow the path through the global symbol table up to the crumb that is missing
+    let mut cur_id = self.parent_id;
+    for crumb in crumbs.iter() {
+      let symtab = match self.repo.syms.get(&cur_id) {
+        Some(symtab) => symtab,
+        None => break
+      };
+      match symtab.get(crumb) {
+        Some(def_id) => cur_id = *def_id,
+        None => {
+          // Private definitions of other modules are not worth suggesting
+          candidates.extend(symtab
+            .keys()
+            .filter(|name| cur_id == self.parent_id || self.repo.is_pub(cur_id, **name))
+            .cloned());
+          return closest_match(*crumb, candidates)
+        }
+      }
+    }
+
+    closest_match(crumbs[0], candidates)
   }
 
   fn resolve_ty(&mut self, ty: &parse::Ty) -> Result<ResolvedTy, ResolveError> {
-    use parse::Ty::*;
-    Ok(match ty {
-      Bool => ResolvedTy::Bool,
-      Uint8 => ResolvedTy::Uint8,
-      Int8 => ResolvedTy::Int8,
-      Uint16 => ResolvedTy::Uint16,
-      Int16 => ResolvedTy::Int16,
-      Uint32 => ResolvedTy::Uint32,
-      Int32 => ResolvedTy::Int32,
-      Uint64 => ResolvedTy::Uint64,
-      Int64 => ResolvedTy::Int64,
-      Uintn => ResolvedTy::Uintn,
-      Intn => ResolvedTy::Intn,
-      Float => ResolvedTy::Float,
-      Double => ResolvedTy::Double,
+    use parse::TyKind::*;
+    let kind = match &ty.kind {
+      Bool => ResolvedTyKind::Bool,
+      Uint8 => ResolvedTyKind::Uint8,
+      Int8 => ResolvedTyKind::Int8,
+      Uint16 => ResolvedTyKind::Uint16,
+      Int16 => ResolvedTyKind::Int16,
+      Uint32 => ResolvedTyKind::Uint32,
+      Int32 => ResolvedTyKind::Int32,
+      Uint64 => ResolvedTyKind::Uint64,
+      Int64 => ResolvedTyKind::Int64,
+      Uintn => ResolvedTyKind::Uintn,
+      Intn => ResolvedTyKind::Intn,
+      Float => ResolvedTyKind::Float,
+      Double => ResolvedTyKind::Double,
       Inst(path, type_args) => {
         // Resolve type arguments
         let type_args = type_args
@@ -434,46 +639,48 @@ impl<'a> ResolveCtx<'a> {
           .monadic_collect2()?;
 
         // Resolve path
-        match self.lookup(path)? {
+        match self.lookup(ty.span, path)? {
           Sym::Def(def_id) => {
             match self.repo.parsed_by_id(def_id) {
-              parse::Def::Type(..) => ResolvedTy::AliasRef(def_id, type_args),
-              parse::Def::Struct(..) => ResolvedTy::StructRef(def_id, type_args),
-              parse::Def::Union(..) => ResolvedTy::UnionRef(def_id, type_args),
-              parse::Def::Enum(..) => ResolvedTy::EnumRef(def_id, type_args),
-              _ => Err(ResolveError::InvalidTypeName(path.clone()))?
+              parse::Def::Type(..) => ResolvedTyKind::AliasRef(def_id, type_args),
+              parse::Def::Struct(..) => ResolvedTyKind::StructRef(def_id, type_args),
+              parse::Def::Union(..) => ResolvedTyKind::UnionRef(def_id, type_args),
+              parse::Def::Enum(..) => ResolvedTyKind::EnumRef(def_id, type_args),
+              _ => Err(ResolveError::InvalidTypeName(ty.span, path.clone()))?
             }
           }
-          Sym::TParam(index) => ResolvedTy::TParam(index),
+          Sym::TParam(index) => ResolvedTyKind::TParam(index),
+          Sym::Module |
           Sym::Local(..) |
           Sym::Binding(..) |
-          Sym::Param(..) => Err(ResolveError::InvalidTypeName(path.clone()))?,
+          Sym::Param(..) => Err(ResolveError::InvalidTypeName(ty.span, path.clone()))?,
         }
       }
       Ptr(is_mut, base_ty) => {
-        ResolvedTy::Ptr(*is_mut, Box::new(self.resolve_ty(base_ty)?))
+        ResolvedTyKind::Ptr(*is_mut, Box::new(self.resolve_ty(base_ty)?))
       }
       Func(params, ret_ty) => {
         let params = self.resolve_params(params)?;
         let ret_ty = self.resolve_ty(ret_ty)?;
-        ResolvedTy::Func(params, Box::new(ret_ty))
+        ResolvedTyKind::Func(params, Box::new(ret_ty))
       }
       Arr(elem_cnt, elem_ty) => {
         let elem_cnt = self.resolve_expr(elem_cnt)?;
         let elem_ty = self.resolve_ty(elem_ty)?;
-        ResolvedTy::Arr(Box::new(elem_cnt), Box::new(elem_ty))
+        ResolvedTyKind::Arr(Box::new(elem_cnt), Box::new(elem_ty))
       }
       Unit => {
-        ResolvedTy::Unit
+        ResolvedTyKind::Unit
       }
       Tuple(params) => {
         let params = self.resolve_params(params)?;
-        ResolvedTy::Tuple(params)
+        ResolvedTyKind::Tuple(params)
       }
-    })
+    };
+    Ok(ResolvedTy { span: ty.span, kind })
   }
 
-  fn resolve_params(&mut self, params: &Vec<(RefStr, parse::Ty)>) -> Result<Vec<(RefStr, ResolvedTy)>, ResolveError> {
+  fn resolve_params(&mut self, params: &[(RefStr, parse::Ty)]) -> Result<Vec<(RefStr, ResolvedTy)>, ResolveError> {
     params
       .iter()
       .map(|(name, ty)| Ok((*name, self.resolve_ty(ty)?)))
@@ -481,72 +688,79 @@ impl<'a> ResolveCtx<'a> {
   }
 
   fn resolve_expr(&mut self, expr: &parse::Expr) -> Result<ResolvedExpr, ResolveError> {
-    use parse::Expr::*;
+    use parse::ExprKind::*;
 
-    Ok(match expr {
+    let kind = match &expr.kind {
       Path(path) => {
-        match self.lookup(path)? {
+        match self.lookup(expr.span, path)? {
           Sym::Def(def_id) => match self.repo.parsed_by_id(def_id) {
             parse::Def::Const(..) => {
-              ResolvedExpr::ConstRef(def_id)
+              ResolvedExprKind::ConstRef(def_id)
             }
             parse::Def::ExternData(..) => {
-              ResolvedExpr::ExternDataRef(def_id)
+              ResolvedExprKind::ExternDataRef(def_id)
             }
             parse::Def::Data(..) => {
-              ResolvedExpr::DataRef(def_id)
+              ResolvedExprKind::DataRef(def_id)
             }
             parse::Def::ExternFunc(..) => {
-              ResolvedExpr::ExternFuncRef(def_id)
+              ResolvedExprKind::ExternFuncRef(def_id)
             }
             parse::Def::Func(..) => {
-              ResolvedExpr::FuncRef(def_id)
+              ResolvedExprKind::FuncRef(def_id)
             }
             parse::Def::Variant(def) => {
-              ResolvedExpr::UnitVariantLit(def.parent_enum, def.variant_index)
+              ResolvedExprKind::UnitVariantLit(def.parent_enum, def.variant_index)
             }
-            _ => Err(ResolveError::InvalidValueName(path.clone()))?
+            _ => Err(ResolveError::InvalidValueName(expr.span, path.clone()))?
           }
-          Sym::Local(index) => ResolvedExpr::LetRef(index),
-          Sym::Param(index) => ResolvedExpr::ParamRef(index),
-          Sym::Binding(index) => ResolvedExpr::BindingRef(index),
-          Sym::TParam(..) => Err(ResolveError::InvalidValueName(path.clone()))?
+          Sym::Local(index) => {
+            self.local_names[index].2 = true;
+            ResolvedExprKind::LetRef(index)
+          }
+          Sym::Param(index) => {
+            self.param_names[index].2 = true;
+            ResolvedExprKind::ParamRef(index)
+          }
+          Sym::Binding(index) => ResolvedExprKind::BindingRef(index),
+          Sym::Module |
+          Sym::TParam(..) => Err(ResolveError::InvalidValueName(expr.span, path.clone()))?
         }
       }
-      Nil => ResolvedExpr::Nil,
-      Bool(val) => ResolvedExpr::Bool(*val),
-      Int(val) => ResolvedExpr::Int(*val),
-      Flt(val) => ResolvedExpr::Flt(*val),
-      Str(val) => ResolvedExpr::Str(val.clone()),
-      CStr(val) => ResolvedExpr::CStr(val.clone()),
-      Unit => ResolvedExpr::Unit,
+      Nil => ResolvedExprKind::Nil,
+      Bool(val) => ResolvedExprKind::Bool(*val),
+      Int(val) => ResolvedExprKind::Int(*val),
+      Flt(val) => ResolvedExprKind::Flt(*val),
+      Str(val) => ResolvedExprKind::Str(val.clone()),
+      CStr(val) => ResolvedExprKind::CStr(val.clone()),
+      Unit => ResolvedExprKind::Unit,
       Tuple(fields) => {
         let fields = fields
           .iter()
           .map(|(name, val)| Ok((*name, self.resolve_expr(val)?)))
           .monadic_collect2()?;
-        ResolvedExpr::TupleLit(fields)
+        ResolvedExprKind::TupleLit(fields)
       }
       Arr(elements) => {
         let elements = elements
           .iter()
           .map(|x| self.resolve_expr(x))
           .monadic_collect2()?;
-        ResolvedExpr::ArrayLit(elements)
+        ResolvedExprKind::ArrayLit(elements)
       }
       Dot(base, field) => {
         let base = self.resolve_expr(base)?;
-        ResolvedExpr::Dot(Box::new(base), *field)
+        ResolvedExprKind::Dot(Box::new(base), *field)
       }
       Index(base, index) => {
         let base = self.resolve_expr(base)?;
         let index = self.resolve_expr(index)?;
-        ResolvedExpr::Index(Box::new(base),
+        ResolvedExprKind::Index(Box::new(base),
                             Box::new(index))
       }
       Ind(ptr) => {
         let ptr = self.resolve_expr(ptr)?;
-        ResolvedExpr::Ind(Box::new(ptr))
+        ResolvedExprKind::Ind(Box::new(ptr))
       }
       Call(called, args) => {
         // Resolve arguments
@@ -557,135 +771,140 @@ impl<'a> ResolveCtx<'a> {
 
         loop {
           // Check for aggregate constructor
-          if let Path(path) = &**called {
-            match self.lookup(path)? {
-              Sym::Def(def_id) => match self.repo.parsed_by_id(def_id) {
+          if let Path(path) = &called.kind {
+            if let Sym::Def(def_id) = self.lookup(called.span, path)? {
+              match self.repo.parsed_by_id(def_id) {
                 parse::Def::Type(..) => { todo!() }
                 parse::Def::Struct(..) => {
-                  break ResolvedExpr::StructLit(def_id, args);
+                  break ResolvedExprKind::StructLit(def_id, args);
                 }
                 parse::Def::Union(..) if args.len() == 1 => {
                   let (name, val) = args.into_iter().nth(0).unwrap();
-                  break ResolvedExpr::UnionLit(def_id,
+                  break ResolvedExprKind::UnionLit(def_id,
                                                name,
                                                Box::new(val));
                 }
                 parse::Def::Union(..) => {
-                  Err(ResolveError::InvalidUnionLiteral)?
+                  Err(ResolveError::InvalidUnionLiteral(expr.span))?
                 }
                 parse::Def::Variant(def) => {
-                  break ResolvedExpr::StructVariantLit(def.parent_enum,
+                  break ResolvedExprKind::StructVariantLit(def.parent_enum,
                                                        def.variant_index,
                                                        args);
                 }
                 _ => ()
               }
-              _ => ()
             }
           }
 
           // Regular call expression
           let called = self.resolve_expr(called)?;
-          break ResolvedExpr::Call(Box::new(called),
+          break ResolvedExprKind::Call(Box::new(called),
                                    args);
         }
       }
       Adr(arg) => {
         let arg = self.resolve_expr(arg)?;
-        ResolvedExpr::Adr(Box::new(arg))
+        ResolvedExprKind::Adr(Box::new(arg))
       }
       Un(op, arg) => {
         let arg = self.resolve_expr(arg)?;
-        ResolvedExpr::Un(*op, Box::new(arg))
+        ResolvedExprKind::Un(*op, Box::new(arg))
       }
       LNot(arg) => {
         let arg = self.resolve_expr(arg)?;
-        ResolvedExpr::LNot(Box::new(arg))
+        ResolvedExprKind::LNot(Box::new(arg))
       }
       Cast(arg, ty) => {
         let arg = self.resolve_expr(arg)?;
         let ty = self.resolve_ty(ty)?;
-        ResolvedExpr::Cast(Box::new(arg), ty)
+        ResolvedExprKind::Cast(Box::new(arg), ty)
       }
       Bin(op, lhs, rhs) => {
         let lhs = self.resolve_expr(lhs)?;
         let rhs = self.resolve_expr(rhs)?;
-        ResolvedExpr::Bin(*op, Box::new(lhs), Box::new(rhs))
+        ResolvedExprKind::Bin(*op, Box::new(lhs), Box::new(rhs))
       }
       LAnd(lhs, rhs) => {
         let lhs = self.resolve_expr(lhs)?;
         let rhs = self.resolve_expr(rhs)?;
-        ResolvedExpr::LAnd(Box::new(lhs), Box::new(rhs))
+        ResolvedExprKind::LAnd(Box::new(lhs), Box::new(rhs))
       }
       LOr(lhs, rhs) => {
         let lhs = self.resolve_expr(lhs)?;
         let rhs = self.resolve_expr(rhs)?;
-        ResolvedExpr::LOr(Box::new(lhs), Box::new(rhs))
+        ResolvedExprKind::LOr(Box::new(lhs), Box::new(rhs))
       }
       Block(body) => {
+        self.check_unreachable(body);
         self.newscope();
         let body = body
           .iter()
-          .map(|expr| self.resolve_expr(expr))
-          .monadic_collect2();
+          .map(|expr| self.resolve_expr_or_error(expr))
+          .collect();
         self.popscope();
 
-        ResolvedExpr::Block(body?)
+        ResolvedExprKind::Block(body)
       }
       As(lhs, rhs) => {
         let lhs = self.resolve_expr(lhs)?;
         let rhs = self.resolve_expr(rhs)?;
-        ResolvedExpr::As(Box::new(lhs), Box::new(rhs))
+        ResolvedExprKind::As(Box::new(lhs), Box::new(rhs))
       }
       Rmw(op, lhs, rhs) => {
         let lhs = self.resolve_expr(lhs)?;
         let rhs = self.resolve_expr(rhs)?;
-        ResolvedExpr::Rmw(*op, Box::new(lhs), Box::new(rhs))
+        ResolvedExprKind::Rmw(*op, Box::new(lhs), Box::new(rhs))
       }
-      Continue => ResolvedExpr::Continue,
+      Continue => ResolvedExprKind::Continue,
       Break(arg) => {
-        let arg = self.resolve_expr(&*arg)?;
-        ResolvedExpr::Break(Box::new(arg))
+        let arg = self.resolve_expr(arg)?;
+        ResolvedExprKind::Break(Box::new(arg))
       }
       Return(arg) => {
-        let arg = self.resolve_expr(&*arg)?;
-        ResolvedExpr::Return(Box::new(arg))
+        let arg = self.resolve_expr(arg)?;
+        ResolvedExprKind::Return(Box::new(arg))
       }
-      Let(name, is_mut, ty, init) => {
+      Let(name_span, name, is_mut, ty, init) => {
         let ty = if let Some(ty) = ty {
           Some(self.resolve_ty(ty)?)
         } else {
           None
         };
-        let init = if let Some(init) = init {
-          Some(Box::new(self.resolve_expr(init)?))
-        } else {
-          None
-        };
+        // NOTE: the local is defined even if its initializer is wrong, so
+        // that its uses are not reported too
+        let init = init.as_ref().map(|init| Box::new(self.resolve_expr_or_error(init)));
+
+        if let Some(previous) = self.local_span(*name) {
+          self.lint.warnings.push(
+            Warning::new(Lint::ShadowedLocals, *name_span, format!("Local {} shadows an earlier definition", name))
+              .labeled(previous, "previously defined here"));
+        }
 
         let index = self.locals.len();
         self.locals.push((*is_mut, ty));
+        self.local_names.push((*name_span, *name, false));
         self.define(*name, Sym::Local(index));
 
-        ResolvedExpr::Let(index, init)
+        ResolvedExprKind::Let(index, init)
       }
       If(cond, tbody, ebody) => {
         let cond = self.resolve_expr(cond)?;
         let tbody = self.resolve_expr(tbody)?;
         let ebody = self.resolve_expr(ebody)?;
-        ResolvedExpr::If(Box::new(cond),
+        ResolvedExprKind::If(Box::new(cond),
                          Box::new(tbody),
                          Box::new(ebody))
       }
       While(cond, body) => {
         let cond = self.resolve_expr(cond)?;
         let body = self.resolve_expr(body)?;
-        ResolvedExpr::While(Box::new(cond),
+        ResolvedExprKind::While(Box::new(cond),
                             Box::new(body))
       }
       Loop(body) => {
         let body = self.resolve_expr(body)?;
-        ResolvedExpr::Loop(Box::new(body))
+        ResolvedExprKind::Loop(Box::new(body))
       }
       Match(cond, cases) => {
         let cond = self.resolve_expr(cond)?;
@@ -694,9 +913,9 @@ impl<'a> ResolveCtx<'a> {
         for (name, variant, val) in cases.iter() {
           self.newscope();
           let index = name.map(|name| {
-            let index = self.bindings;
+            let index = self.bindings.len();
             self.define(name, Sym::Binding(index));
-            self.bindings += 1;
+            self.bindings.push((val.span, name));
             index
           });
           let result = self.resolve_expr(val);
@@ -704,29 +923,114 @@ impl<'a> ResolveCtx<'a> {
           resolved_cases.push((index, *variant, result?));
         }
 
-        ResolvedExpr::Match(Box::new(cond),
+        ResolvedExprKind::Match(Box::new(cond),
                             resolved_cases)
       }
+    };
+    Ok(ResolvedExpr::new(expr.span, kind))
+  }
+
+  /// Find where the parameter or local variable visible with a name was defined
+  fn local_span(&self, name: RefStr) -> Option<Span> {
+    for scope in self.scopes.iter().rev() {
+      match scope.get(&name) {
+        Some(Sym::Param(index)) => return Some(self.param_names[*index].0),
+        Some(Sym::Local(index)) => return Some(self.local_names[*index].0),
+        Some(..) => return None,
+        None => ()
+      }
+    }
+    None
+  }
+
+  /// Resolve an expression, recording the error and carrying on if it fails
+  fn resolve_expr_or_error(&mut self, expr: &parse::Expr) -> ResolvedExpr {
+    self.resolve_expr(expr).unwrap_or_else(|error| {
+      self.errors.push(error);
+      ResolvedExpr::new(expr.span, ResolvedExprKind::Error)
     })
   }
+
+  /// Warn about parameters and local variables that were never used
+  fn check_unused(&mut self) {
+    for (span, name, used) in self.param_names.iter().chain(self.local_names.iter()) {
+      // Names starting with an underscore are unused on purpose
+      if !used && !name.borrow_rs().starts_with('_') {
+        self.lint.warnings.push(
+          Warning::new(Lint::UnusedVariables, *span, format!("Unused variable {}", name)));
+      }
+    }
+  }
+
+  /// Warn about expressions following a return, break or continue in a block
+  fn check_unreachable(&mut self, body: &[parse::Expr]) {
+    use parse::ExprKind::*;
+
+    let index = match body.iter().position(|expr| matches!(expr.kind, Return(..) | Break(..) | Continue)) {
+      Some(index) => index,
+      None => return
+    };
+
+    // NOTE: semicolons show up as unit expressions, these are not worth a warning
+    let mut unreachable = body[index + 1..].iter().filter(|expr| !matches!(expr.kind, Unit));
+    if let Some(first) = unreachable.next() {
+      let last = unreachable.next_back().unwrap_or(first);
+      self.lint.warnings.push(
+        Warning::new(Lint::UnreachableCode, first.span.to(last.span), "Unreachable code".to_string())
+          .labeled(body[index].span, "any code following this expression is unreachable"));
+    }
+  }
 }
 
 /// Errors
 #[derive(Debug)]
-enum ResolveError {
-  UnresolvedPath(parse::Path),
-  InvalidValueName(parse::Path),
-  InvalidTypeName(parse::Path),
-  InvalidUnionLiteral,
+pub(crate) enum ResolveError {
+  UnresolvedPath(Span, parse::Path, Option<RefStr>),
+  InvalidValueName(Span, parse::Path),
+  InvalidTypeName(Span, parse::Path),
+  InvalidUnionLiteral(Span),
+  UnresolvedImport(Span, RefStr, Option<RefStr>),
+  // Reference to a private definition of another module, with the location of the definition
+  PrivateDefinition(Span, RefStr, Span),
+  GenericExport(Span, RefStr),
+}
+
+impl ResolveError {
+  pub fn span(&self) -> Span {
+    match self {
+      ResolveError::UnresolvedPath(span, ..) |
+      ResolveError::InvalidValueName(span, ..) |
+      ResolveError::InvalidTypeName(span, ..) |
+      ResolveError::InvalidUnionLiteral(span) |
+      ResolveError::UnresolvedImport(span, ..) |
+      ResolveError::PrivateDefinition(span, ..) |
+      ResolveError::GenericExport(span, ..) => *span
+    }
+  }
+
+  pub fn code(&self) -> &'static str {
+    match self {
+      ResolveError::UnresolvedPath(..) => "E0101",
+      ResolveError::InvalidValueName(..) => "E0102",
+      ResolveError::InvalidTypeName(..) => "E0103",
+      ResolveError::InvalidUnionLiteral(..) => "E0104",
+      ResolveError::UnresolvedImport(..) => "E0105",
+      ResolveError::PrivateDefinition(..) => "E0106",
+      ResolveError::GenericExport(..) => "E0107"
+    }
+  }
 }
 
 impl fmt::Display for ResolveError {
   fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
     match self {
-      ResolveError::UnresolvedPath(path) => write!(f, "Unresolved path {}", path),
-      ResolveError::InvalidValueName(path) => write!(f, "{} does not refer to a value", path),
-      ResolveError::InvalidTypeName(path) => write!(f, "{} does not refer to a type", path),
-      ResolveError::InvalidUnionLiteral => write!(f, "Union literal with more than one argument")
+      ResolveError::UnresolvedPath(_, path, _) => write!(f, "Unresolved path {}", path),
+      ResolveError::InvalidValueName(_, path) => write!(f, "{} does not refer to a value", path),
+      ResolveError::InvalidTypeName(_, path) => write!(f, "{} does not refer to a type", path),
+      ResolveError::InvalidUnionLiteral(..) => write!(f, "Union literal with more than one argument"),
+      ResolveError::UnresolvedImport(_, name, _) => write!(f, "Unresolved import {}", name),
+      ResolveError::PrivateDefinition(_, name, _) => write!(f, "{} is private", name),
+      ResolveError::GenericExport(_, name) => write!(f, "Generic function {} cannot be exported", name)
     }
   }
 }
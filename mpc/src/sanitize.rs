@@ -0,0 +1,50 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+use std::fmt::{self, Formatter};
+use std::str::FromStr;
+
+/// Sanitizers programs can be instrumented with
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Sanitizer {
+  Address,
+  Undefined
+}
+
+impl Sanitizer {
+  pub const ALL: [&'static str; 2] = [ "address", "undefined" ];
+
+  /// Check if a runtime needs to be linked in for the sanitizer
+  pub fn has_runtime(&self) -> bool {
+    match self {
+      Sanitizer::Address => true,
+      // Undefined behaviour is caught by the runtime checks, reporting failures themselves
+      Sanitizer::Undefined => false
+    }
+  }
+}
+
+impl fmt::Display for Sanitizer {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    let name = match self {
+      Sanitizer::Address => "address",
+      Sanitizer::Undefined => "undefined"
+    };
+    write!(f, "{}", name)
+  }
+}
+
+impl FromStr for Sanitizer {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "address" => Ok(Sanitizer::Address),
+      "undefined" => Ok(Sanitizer::Undefined),
+      _ => Err(format!("Unknown sanitizer {}", s))
+    }
+  }
+}
@@ -35,14 +35,20 @@ pub enum ConstVal {
   BoolLit { val: bool },
   IntLit { ty: Ty, val: isize },
   FltLit { ty: Ty, val: f64 },
-  ArrLit { ty: Ty, vals: Vec<ConstVal> },
-  StructLit { ty: Ty, vals: Vec<ConstVal> },
+  ArrLit { #[allow(dead_code)] ty: Ty, vals: Vec<ConstVal> },
+  StructLit { #[allow(dead_code)] ty: Ty, vals: Vec<ConstVal> },
   UnionLit { ty: Ty, val: Box<ConstVal> },
   CStrLit { val: Vec<u8> },
 }
 
 #[derive(Debug)]
-struct InvalidConstantExpressionError;
+pub(crate) struct InvalidConstantExpressionError(pub Span);
+
+impl InvalidConstantExpressionError {
+  pub fn code(&self) -> &'static str {
+    "E0301"
+  }
+}
 
 impl fmt::Display for InvalidConstantExpressionError {
   fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -68,7 +74,7 @@ fn eval_constptr(lvalue: &LValue) -> MRes<ConstPtr> {
       })
     }
     LValue::StruDot { ty, arg, idx, .. } => {
-      let base = eval_constptr(&*arg)?;
+      let base = eval_constptr(arg)?;
       Ok(StructField {
         ty: ty.clone(),
         base: Box::new(base),
@@ -76,14 +82,14 @@ fn eval_constptr(lvalue: &LValue) -> MRes<ConstPtr> {
       })
     }
     LValue::UnionDot { ty, arg, .. } => {
-      let base = eval_constptr(&*arg)?;
+      let base = eval_constptr(arg)?;
       Ok(UnionField {
         ty: ty.clone(),
         base: Box::new(base)
       })
     }
     LValue::Index { ty, arg, idx, .. } => {
-      let base = eval_constptr(&*arg)?;
+      let base = eval_constptr(arg)?;
       let index = consteval_index(idx)?;
       Ok(ArrayElement {
         ty: ty.clone(),
@@ -92,7 +98,7 @@ fn eval_constptr(lvalue: &LValue) -> MRes<ConstPtr> {
       })
     }
     _ => {
-      Err(Box::new(InvalidConstantExpressionError))
+      Err(Box::new(InvalidConstantExpressionError(lvalue.span())))
     }
   }
 }
@@ -123,7 +129,7 @@ pub(super) fn eval_constload(lvalue: &LValue) -> MRes<ConstVal> {
         ConstVal::StructLit { vals, .. } => {
           Ok(vals.into_iter().nth(*idx).unwrap())
         }
-        _ => Err(Box::new(InvalidConstantExpressionError))
+        _ => Err(Box::new(InvalidConstantExpressionError(lvalue.span())))
       }
     }
     LValue::UnionDot { arg, .. } => {
@@ -132,7 +138,7 @@ pub(super) fn eval_constload(lvalue: &LValue) -> MRes<ConstVal> {
         ConstVal::UnionLit { .. } => {
           todo!()
         }
-        _ => Err(Box::new(InvalidConstantExpressionError))
+        _ => Err(Box::new(InvalidConstantExpressionError(lvalue.span())))
       }
     }
     LValue::Index { arg, idx, .. } => {
@@ -142,11 +148,11 @@ pub(super) fn eval_constload(lvalue: &LValue) -> MRes<ConstVal> {
         ConstVal::ArrLit { vals, .. } if index < vals.len() => {
           Ok(vals.into_iter().nth(index).unwrap())
         }
-        _ => Err(Box::new(InvalidConstantExpressionError))
+        _ => Err(Box::new(InvalidConstantExpressionError(lvalue.span())))
       }
     }
     _ => {
-      Err(Box::new(InvalidConstantExpressionError))
+      Err(Box::new(InvalidConstantExpressionError(lvalue.span())))
     }
   }
 }
@@ -188,13 +194,13 @@ pub(super) fn consteval(rvalue: &RValue) -> MRes<ConstVal> {
         (UMinus, IntLit { ty, val, .. }) => Ok(IntLit { ty, val: -val }),
         (UMinus, FltLit { ty, val, .. }) => Ok(FltLit { ty, val: -val }),
         (Not, BoolLit { val }) => Ok(BoolLit { val: !val }),
-        _ => Err(Box::new(InvalidConstantExpressionError))
+        _ => Err(Box::new(InvalidConstantExpressionError(rvalue.span())))
       }
     }
     RValue::LNot { arg, .. } => {
       match consteval(arg)? {
         BoolLit { val } => Ok(BoolLit { val: !val }),
-        _ => Err(Box::new(InvalidConstantExpressionError))
+        _ => Err(Box::new(InvalidConstantExpressionError(rvalue.span())))
       }
     }
     RValue::Bin { op, lhs, rhs, .. } => {
@@ -225,32 +231,32 @@ pub(super) fn consteval(rvalue: &RValue) -> MRes<ConstVal> {
         (Gt, FltLit { val: lhs, .. }, FltLit { val: rhs, .. }) => Ok(BoolLit { val: lhs > rhs }),
         (Le, FltLit { val: lhs, .. }, FltLit { val: rhs, .. }) => Ok(BoolLit { val: lhs <= rhs }),
         (Ge, FltLit { val: lhs, .. }, FltLit { val: rhs, .. }) => Ok(BoolLit { val: lhs >= rhs }),
-        _ => Err(Box::new(InvalidConstantExpressionError))
+        _ => Err(Box::new(InvalidConstantExpressionError(rvalue.span())))
       }
     }
     RValue::LAnd { lhs, rhs, .. } => {
       match consteval(lhs)? {
         BoolLit { val: true } => Ok(BoolLit { val: true }),
         BoolLit { val: false } => consteval(rhs),
-        _ => Err(Box::new(InvalidConstantExpressionError))
+        _ => Err(Box::new(InvalidConstantExpressionError(rvalue.span())))
       }
     }
     RValue::LOr { lhs, rhs, .. } => {
       match consteval(lhs)? {
         BoolLit { val: true } => consteval(rhs),
         BoolLit { val: false } => Ok(BoolLit { val: false }),
-        _ => Err(Box::new(InvalidConstantExpressionError))
+        _ => Err(Box::new(InvalidConstantExpressionError(rvalue.span())))
       }
     }
     RValue::If { cond, tbody, ebody, .. } => {
       match consteval(cond)? {
         BoolLit { val: true } => consteval(tbody),
         BoolLit { val: false } => consteval(ebody),
-        _ => Err(Box::new(InvalidConstantExpressionError))
+        _ => Err(Box::new(InvalidConstantExpressionError(rvalue.span())))
       }
     }
     _ => {
-      Err(Box::new(InvalidConstantExpressionError))
+      Err(Box::new(InvalidConstantExpressionError(rvalue.span())))
     }
   }
 }
@@ -258,6 +264,6 @@ pub(super) fn consteval(rvalue: &RValue) -> MRes<ConstVal> {
 pub(super) fn consteval_index(rvalue: &RValue) -> MRes<usize> {
   match consteval(rvalue)? {
     ConstVal::IntLit { val, .. } if val >= 0 => Ok(val as usize),
-    _ => Err(Box::new(InvalidConstantExpressionError)),
+    _ => Err(Box::new(InvalidConstantExpressionError(rvalue.span()))),
   }
 }
\ No newline at end of file
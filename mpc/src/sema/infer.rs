@@ -13,13 +13,24 @@ pub(super) fn infer(repo: &Repository, tctx: &mut TVarCtx) -> MRes<HashMap<(DefI
     repo,
     tctx,
     insts: HashMap::new(),
+    errors: Vec::new(),
   };
 
-  // Instantiate signatures for non-generic functions
-  for (id, def) in repo.resolved_defs.iter() {
-    match def {
+  // Instantiate signatures for non-generic functions, and exported data,
+  // as it might be referred to from other object files
+  let mut def_ids: Vec<DefId> = repo.resolved_defs.keys().cloned().collect();
+  def_ids.sort();
+  for id in def_ids.into_iter() {
+    match ctx.resolved_def(id) {
       ResolvedDef::Func(def) if def.type_params == 0 => {
-        ctx.inst_func_sig((*id, Vec::new()))?;
+        if let Err(error) = ctx.inst_func_sig(def.span, (id, Vec::new())) {
+          ctx.errors.push(error);
+        }
+      }
+      ResolvedDef::Data(def) if def.is_export == IsExport::Yes => {
+        if let Err(error) = ctx.inst_data(def.span, id) {
+          ctx.errors.push(error);
+        }
       }
       _ => ()
     }
@@ -27,7 +38,7 @@ pub(super) fn infer(repo: &Repository, tctx: &mut TVarCtx) -> MRes<HashMap<(DefI
 
   loop {
     // De-duplicate signatures after each pass
-    for ((def_id, type_args), inst) in std::mem::replace(&mut ctx.insts, HashMap::new()).into_iter() {
+    for ((def_id, type_args), inst) in std::mem::take(&mut ctx.insts).into_iter() {
       ctx.insts.insert((def_id, ctx.tctx.canonical_type_args(&type_args)), inst);
     }
 
@@ -39,15 +50,23 @@ pub(super) fn infer(repo: &Repository, tctx: &mut TVarCtx) -> MRes<HashMap<(DefI
       }
     }
 
-    if queue.len() == 0 { break; }
+    if queue.is_empty() { break; }
+
+    // Sort the queue, so errors are reported in a fixed order
+    queue.sort_by_key(|(def_id, type_args)| (*def_id, format!("{:?}", type_args)));
 
     for (def_id, type_args) in queue.into_iter() {
-      ctx.inst_func_body((def_id, type_args))?;
+      if let Err(error) = ctx.inst_func_body((def_id, type_args.clone())) {
+        ctx.errors.push(error);
+        ctx.poison_func_body((def_id, type_args));
+      }
     }
   }
 
+  ErrorList::check(std::mem::take(&mut ctx.errors))?;
+
   // Final de-duplication pass to get rid of type variables in instance IDs
-  for ((def_id, type_args), inst) in std::mem::replace(&mut ctx.insts, HashMap::new()).into_iter() {
+  for ((def_id, type_args), inst) in std::mem::take(&mut ctx.insts).into_iter() {
     ctx.insts.insert((def_id, ctx.tctx.final_type_args(&type_args)), inst);
   }
 
@@ -61,6 +80,8 @@ struct GlobalCtx<'repo, 'tctx> {
   tctx: &'tctx mut TVarCtx,
   // Checked definitions
   insts: HashMap<(DefId, Vec<Ty>), Inst>,
+  // Errors reported so far
+  errors: Vec<Box<dyn error::Error + Send + Sync>>,
 }
 
 impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
@@ -71,7 +92,7 @@ impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
 
   /// Lookup an instance by its id
   fn find_inst(&mut self, id: &(DefId, Vec<Ty>)) -> &Inst {
-    for ((def_id, type_args), inst) in std::mem::replace(&mut self.insts, HashMap::new()).into_iter() {
+    for ((def_id, type_args), inst) in std::mem::take(&mut self.insts).into_iter() {
       self.insts.insert((def_id, self.tctx.canonical_type_args(&type_args)), inst);
     }
     self.insts.get(id).unwrap()
@@ -83,13 +104,13 @@ impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
     DefCtx::new(self, id.1).infer_ty(&def.ty)
   }
 
-  fn inst_struct(&mut self, id: (DefId, Vec<Ty>)) -> MRes<Ty> {
+  fn inst_struct(&mut self, span: Span, id: (DefId, Vec<Ty>)) -> MRes<Ty> {
     let def = self.resolved_def(id.0).unwrap_struct();
-    if let Some(..) = self.insts.get(&id) { return Ok(Ty::StructRef(def.name, id)); }
+    if self.insts.contains_key(&id) { return Ok(Ty::StructRef(def.name, id)); }
 
     self.insts.insert(id.clone(), Inst::Struct { name: def.name, params: None });
     if def.type_params != id.1.len() {
-      return Err(Box::new(TypeError(format!("Incorrect number of type parameters"))))
+      return Err(Box::new(TypeError::WrongTypeArgCount(span, def.span)))
     }
     let mut def_ctx = DefCtx::new(self, id.1.clone());
     let params = def_ctx.infer_params(&def.params)?;
@@ -98,13 +119,13 @@ impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
     Ok(Ty::StructRef(def.name, id))
   }
 
-  fn inst_union(&mut self, id: (DefId, Vec<Ty>)) -> MRes<Ty> {
+  fn inst_union(&mut self, span: Span, id: (DefId, Vec<Ty>)) -> MRes<Ty> {
     let def = self.resolved_def(id.0).unwrap_union();
-    if let Some(..) = self.insts.get(&id) { return Ok(Ty::UnionRef(def.name, id)); }
+    if self.insts.contains_key(&id) { return Ok(Ty::UnionRef(def.name, id)); }
 
     self.insts.insert(id.clone(), Inst::Union { name: def.name, params: None });
     if def.type_params != id.1.len() {
-      return Err(Box::new(TypeError(format!("Incorrect number of type parameters"))))
+      return Err(Box::new(TypeError::WrongTypeArgCount(span, def.span)))
     }
     let mut def_ctx = DefCtx::new(self, id.1.clone());
     let params = def_ctx.infer_params(&def.params)?;
@@ -113,13 +134,13 @@ impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
     Ok(Ty::UnionRef(def.name, id))
   }
 
-  fn inst_enum(&mut self, id: (DefId, Vec<Ty>)) -> MRes<Ty> {
+  fn inst_enum(&mut self, span: Span, id: (DefId, Vec<Ty>)) -> MRes<Ty> {
     let def = self.resolved_def(id.0).unwrap_enum();
-    if let Some(..) = self.insts.get(&id) { return Ok(Ty::EnumRef(def.name, id)); }
+    if self.insts.contains_key(&id) { return Ok(Ty::EnumRef(def.name, id)); }
 
     self.insts.insert(id.clone(), Inst::Enum { name: def.name, variants: None });
     if def.type_params != id.1.len() {
-      return Err(Box::new(TypeError(format!("Incorrect number of type parameters"))))
+      return Err(Box::new(TypeError::WrongTypeArgCount(span, def.span)))
     }
 
     let mut def_ctx = DefCtx::new(self, id.1.clone());
@@ -129,34 +150,42 @@ impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
     Ok(Ty::EnumRef(def.name, id))
   }
 
-  fn inst_data(&mut self, id: DefId) -> MRes<LValue> {
+  fn inst_data(&mut self, span: Span, id: DefId) -> MRes<LValue> {
     let def = self.resolved_def(id).unwrap_data();
 
     let mut def_ctx = DefCtx::new(self, Vec::new());
 
     let ty = def_ctx.infer_ty(&def.ty)?;
     let init = def_ctx.infer_rvalue(&def.init)?;
-    self.tctx.unify(&ty, init.ty())?;
+    self.tctx.unify(init.span(), &ty, init.ty())
+      .map_err(|error| error.annotated(def.ty.span))?;
 
     self.insts.insert((id, vec![]), Inst::Data {
       name: def.name,
+      symbol: def.name,
+      is_export: def.is_export,
       ty: ty.clone(),
       is_mut: def.is_mut,
       init: consteval(&init)?,
     });
 
-    Ok(LValue::DataRef { ty, is_mut: def.is_mut, id })
+    Ok(LValue::DataRef { span, ty, is_mut: def.is_mut, id })
   }
 
-  fn inst_func_sig(&mut self, id: (DefId, Vec<Ty>)) -> MRes<RValue> {
+  fn inst_func_sig(&mut self, span: Span, id: (DefId, Vec<Ty>)) -> MRes<RValue> {
     let def = self.resolved_def(id.0).unwrap_func();
 
+    // Check type argument count
+    if def.type_params != id.1.len() {
+      return Err(Box::new(TypeError::WrongTypeArgCount(span, def.span)));
+    }
+
     // Crate context
     let mut def_ctx = DefCtx::new(self, id.1.clone());
 
     // Parameters
     let mut param_tys = vec![];
-    for (name, _, ty) in def.params.iter() {
+    for (_, name, _, ty) in def.params.iter() {
       let ty = def_ctx.infer_ty(ty)?;
       param_tys.push((*name, ty.clone()));
     }
@@ -165,9 +194,12 @@ impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
     let ret_ty = def_ctx.infer_ty(&def.ret_ty)?;
 
     // Insert signature record
-    if let None = self.insts.get(&id) {
+    if !self.insts.contains_key(&id) {
       self.insts.insert(id.clone(), Inst::Func {
+        span: def.span,
         name: def.name,
+        symbol: def.name,
+        is_export: def.is_export,
         ty: Ty::Func(param_tys.clone(), false, Box::new(ret_ty.clone())),
         params: Vec::new(),
         locals: Vec::new(),
@@ -178,6 +210,7 @@ impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
 
     // Return reference to signature
     Ok(RValue::FuncRef {
+      span,
       ty: Ty::Func(param_tys.clone(), false, Box::new(ret_ty.clone())),
       id
     })
@@ -186,11 +219,6 @@ impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
   fn inst_func_body(&mut self, id: (DefId, Vec<Ty>)) -> MRes<()> {
     let def = self.resolved_def(id.0).unwrap_func();
 
-    // check type argument count
-    if def.type_params != id.1.len() {
-      return Err(Box::new(TypeError(format!("Incorrect number of type parameters"))));
-    }
-
     // Setup context
     let mut def_ctx = DefCtx::new(self, id.1.clone());
 
@@ -199,14 +227,14 @@ impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
 
     // Function parameters
     let mut param_tys = vec![];
-    for (name, is_mut, ty) in def.params.iter() {
+    for (_, name, is_mut, ty) in def.params.iter() {
       let ty = def_ctx.infer_ty(ty)?;
       param_tys.push((*name, ty.clone()));
       def_ctx.params.push((*is_mut, ty));
     }
 
     // Local variables
-    for (is_mut, ty) in def.locals.iter() {
+    for (_, _, is_mut, ty) in def.locals.iter() {
       let ty = if let Some(ty) = ty {
         def_ctx.infer_ty(ty)?
       } else {
@@ -214,22 +242,42 @@ impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
       };
       def_ctx.locals.push((*is_mut, ty));
     }
+    def_ctx.local_annotations = def.locals
+      .iter()
+      .map(|(_, _, _, ty)| ty.as_ref().map(|ty| ty.span))
+      .collect();
 
     // Return type
     let ret_ty = def_ctx.infer_ty(&def.ret_ty)?;
-    def_ctx.ret_ty = Some(ret_ty.clone());
+    def_ctx.ret_ty = Some((ret_ty.clone(), def.ret_ty.span));
 
     // Body
     let body = def_ctx.infer_rvalue(&def.body)?;
-    def_ctx.global.tctx.unify(&ret_ty, body.ty())?;
+    def_ctx.global.tctx.unify(body.span(), &ret_ty, body.ty())
+      .map_err(|error| error.annotated(def.ret_ty.span))?;
 
     // Insert body
     let inst = Inst::Func {
+      span: def.span,
       name: def.name,
+      symbol: def.name,
+      is_export: def.is_export,
       ty: Ty::Func(param_tys.clone(), false, Box::new(ret_ty.clone())),
-      params: def_ctx.params,
-      locals: def_ctx.locals,
-      bindings: def_ctx.bindings,
+      params: def.params
+        .iter()
+        .zip(def_ctx.params)
+        .map(|((span, name, ..), (is_mut, ty))| (*span, *name, is_mut, ty))
+        .collect(),
+      locals: def.locals
+        .iter()
+        .zip(def_ctx.locals)
+        .map(|((span, name, ..), (is_mut, ty))| (*span, *name, is_mut, ty))
+        .collect(),
+      bindings: def.bindings
+        .iter()
+        .zip(def_ctx.bindings)
+        .map(|((span, name), (is_mut, ty))| (*span, *name, is_mut, ty))
+        .collect(),
       body: Some(body),
     };
     self.insts.insert(id.clone(), inst);
@@ -237,16 +285,23 @@ impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
     Ok(())
   }
 
-  fn inst_extern_data(&mut self, id: DefId) -> MRes<LValue> {
+  /// Mark the body of a function that failed to type check as done, keeping its signature
+  fn poison_func_body(&mut self, id: (DefId, Vec<Ty>)) {
+    if let Some(Inst::Func { span, body, .. }) = self.insts.get_mut(&id) {
+      *body = Some(RValue::Error { span: *span, ty: Ty::Error });
+    }
+  }
+
+  fn inst_extern_data(&mut self, span: Span, id: DefId) -> MRes<LValue> {
     let def = self.resolved_def(id).unwrap_extern_data();
 
     let ty = DefCtx::new(self, Vec::new()).infer_ty(&def.ty)?;
     self.insts.insert((id, vec![]), Inst::ExternData { name: def.name, ty: ty.clone(), is_mut: def.is_mut });
 
-    Ok(LValue::DataRef { ty, is_mut: def.is_mut, id })
+    Ok(LValue::DataRef { span, ty, is_mut: def.is_mut, id })
   }
 
-  fn inst_extern_func(&mut self, id: DefId) -> MRes<RValue> {
+  fn inst_extern_func(&mut self, span: Span, id: DefId) -> MRes<RValue> {
     let def = self.resolved_def(id).unwrap_extern_func();
 
     let mut def_ctx = DefCtx::new(self, Vec::new());
@@ -255,7 +310,7 @@ impl<'repo, 'tctx> GlobalCtx<'repo, 'tctx> {
                       Box::new(def_ctx.infer_ty(&def.ret_ty)?));
     self.insts.insert((id, vec![]), Inst::ExternFunc { name: def.name, ty: ty.clone() });
 
-    Ok(RValue::FuncRef { ty, id: (id, vec![]) })
+    Ok(RValue::FuncRef { span, ty, id: (id, vec![]) })
   }
 }
 
@@ -268,10 +323,12 @@ struct DefCtx<'global, 'repo, 'tctx> {
   params: Vec<(IsMut, Ty)>,
   // Let bindings
   locals: Vec<(IsMut, Ty)>,
+  // Type annotations of let bindings
+  local_annotations: Vec<Option<Span>>,
   // Enum variant bindings
   bindings: Vec<(IsMut, Ty)>,
-  // Function return type
-  ret_ty: Option<Ty>,
+  // Function return type, and its annotation
+  ret_ty: Option<(Ty, Span)>,
   // Loop break type
   loop_ty: Vec<Ty>,
 }
@@ -283,6 +340,7 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
       type_args,
       params: Vec::new(),
       locals: Vec::new(),
+      local_annotations: Vec::new(),
       bindings: Vec::new(),
       ret_ty: None,
       loop_ty: Vec::new(),
@@ -292,8 +350,8 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
 
   /// Infer the semantic form of a type expression
   fn infer_ty(&mut self, ty: &ResolvedTy) -> MRes<Ty> {
-    use ResolvedTy::*;
-    Ok(match ty {
+    use ResolvedTyKind::*;
+    Ok(match &ty.kind {
       Bool => Ty::Bool,
       Uint8 => Ty::Uint8,
       Int8 => Ty::Int8,
@@ -316,15 +374,15 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
       }
       StructRef(def_id, type_args) => {
         let type_args = self.infer_type_args(type_args)?;
-        self.global.inst_struct((*def_id, type_args))?
+        self.global.inst_struct(ty.span, (*def_id, type_args))?
       }
       UnionRef(def_id, type_args) => {
         let type_args = self.infer_type_args(type_args)?;
-        self.global.inst_union((*def_id, type_args))?
+        self.global.inst_union(ty.span, (*def_id, type_args))?
       }
       EnumRef(def_id, type_args) => {
         let type_args = self.infer_type_args(type_args)?;
-        self.global.inst_enum((*def_id, type_args))?
+        self.global.inst_enum(ty.span, (*def_id, type_args))?
       }
       Ptr(is_mut, base_ty) => {
         Ty::Ptr(*is_mut, Box::new(self.infer_ty(base_ty)?))
@@ -355,7 +413,7 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
     Ok(result)
   }
 
-  fn infer_variants(&mut self, variants: &Vec<ResolvedVariant>) -> MRes<Vec<Variant>> {
+  fn infer_variants(&mut self, variants: &[ResolvedVariant]) -> MRes<Vec<Variant>> {
     let mut result = vec![];
     for variant in variants.iter() {
       result.push(match variant {
@@ -370,7 +428,7 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
     Ok(result)
   }
 
-  fn infer_type_args(&mut self, type_args:&Vec<ResolvedTy>) -> MRes<Vec<Ty>> {
+  fn infer_type_args(&mut self, type_args:&[ResolvedTy]) -> MRes<Vec<Ty>> {
     type_args
       .iter()
       .map(|ty| self.infer_ty(ty))
@@ -379,26 +437,30 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
 
   /// Infer the semantic form of an expression in an lvalue context
   fn infer_lvalue(&mut self, expr: &ResolvedExpr) -> MRes<LValue> {
-    use ResolvedExpr::*;
+    use ResolvedExprKind::*;
 
-    Ok(match expr {
+    let span = expr.span;
+    Ok(match &expr.kind {
       ConstRef(def_id) => {
         let def = self.global.resolved_def(*def_id).unwrap_const();
         self.infer_lvalue(&def.val)?
       }
-      DataRef(def_id) => self.global.inst_data(*def_id)?,
-      ExternDataRef(def_id) => self.global.inst_extern_data(*def_id)?,
+      DataRef(def_id) => self.global.inst_data(span, *def_id)?,
+      ExternDataRef(def_id) => self.global.inst_extern_data(span, *def_id)?,
       ParamRef(index) => LValue::ParamRef {
+        span,
         ty: self.params[*index].1.clone(),
         is_mut: self.params[*index].0,
         index: *index
       },
       LetRef(index) => LValue::LetRef {
+        span,
         ty: self.locals[*index].1.clone(),
         is_mut: self.locals[*index].0,
         index: *index
       },
       BindingRef(index) => LValue::BindingRef {
+        span,
         ty: self.bindings[*index].1.clone(),
         is_mut: self.bindings[*index].0,
         index: *index
@@ -412,6 +474,7 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
           fields.push(val);
         }
         LValue::TupleLit {
+          span,
           ty: Ty::Tuple(params),
           is_mut: IsMut::No,
           fields
@@ -423,9 +486,10 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
           .map(|element| self.infer_rvalue(element))
           .monadic_collect()?;
         for element in elements.iter() {
-          self.global.tctx.unify(&elem_ty, element.ty())?;
+          self.global.tctx.unify(element.span(), &elem_ty, element.ty())?;
         }
         LValue::ArrayLit {
+          span,
           ty: Ty::Arr(elements.len(), Box::new(elem_ty)),
           is_mut: IsMut::No,
           elements
@@ -436,10 +500,11 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
         let type_args: Vec<Ty> = (0..def.type_params)
           .map(|_| self.global.tctx.new_var(Bound::Any))
           .collect();
-        let ty = self.global.inst_struct((*def_id, type_args.clone()))?;
+        let ty = self.global.inst_struct(span, (*def_id, type_args.clone()))?;
         let (_, params) = self.global.find_inst(&(*def_id, type_args)).unwrap_struct();
         let params = params.clone();
         LValue::StructLit {
+          span,
           ty,
           is_mut: IsMut::No,
           fields: self.infer_args(&params, fields)?
@@ -453,19 +518,22 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
         let type_args: Vec<Ty> = (0..def.type_params)
           .map(|_| self.global.tctx.new_var(Bound::Any))
           .collect();
-        let ty = self.global.inst_union((*def_id, type_args.clone()))?;
+        let ty = self.global.inst_union(span, (*def_id, type_args.clone()))?;
         let (_, params) = self.global.find_inst(&(*def_id, type_args)).unwrap_union();
         let params = params.clone();
         // Find which field the value belongs to
-        if name.borrow_rs() == "" && params.len() > 0 {
-          self.global.tctx.unify(val.ty(), &params[0].1)?;
+        if name.borrow_rs() == "" && !params.is_empty() {
+          self.global.tctx.unify(val.span(), val.ty(), &params[0].1)?;
         } else if let Some((_, param_ty)) = lin_search(&params, name) {
-          self.global.tctx.unify(val.ty(), param_ty)?;
+          self.global.tctx.unify(val.span(), val.ty(), param_ty)?;
         } else {
-          Err(Box::new(TypeError(format!("Unknown union field {}", name))))?
+          let suggestion = closest_match(*name, params.iter().map(|(name, _)| *name));
+          let ty = self.global.tctx.canonical_ty(&ty);
+          Err(Box::new(TypeError::UnknownField(span, ty, *name, suggestion)))?
         }
 
         LValue::UnionLit {
+          span,
           ty,
           is_mut: IsMut::No,
           field: val
@@ -476,27 +544,28 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
         let type_args: Vec<Ty> = (0..def.type_params)
           .map(|_| self.global.tctx.new_var(Bound::Any))
           .collect();
-        let ty = self.global.inst_enum((*def_id, type_args.clone()))?;
+        let ty = self.global.inst_enum(span, (*def_id, type_args.clone()))?;
         let (_, variants) = self.global.find_inst(&(*def_id, type_args)).unwrap_enum();
         let variants = variants.clone();
         match &variants[*index] {
           Variant::Unit(..) => (),
-          Variant::Struct(..) => Err(Box::new(TypeError(format!("Expected arguments for struct variant"))))?
+          Variant::Struct(..) => Err(Box::new(TypeError::MissingVariantArguments(span)))?
         }
-        LValue::UnitVariantLit { ty, is_mut: IsMut::No, index: *index }
+        LValue::UnitVariantLit { span, ty, is_mut: IsMut::No, index: *index }
       }
       StructVariantLit(def_id, index, fields) => {
         let def = self.global.resolved_def(*def_id).unwrap_enum();
         let type_args: Vec<Ty> = (0..def.type_params)
           .map(|_| self.global.tctx.new_var(Bound::Any))
           .collect();
-        let ty = self.global.inst_enum((*def_id, type_args.clone()))?;
+        let ty = self.global.inst_enum(span, (*def_id, type_args.clone()))?;
         let (_, variants) = self.global.find_inst(&(*def_id, type_args)).unwrap_enum();
         let variants = variants.clone();
         match &variants[*index] {
-          Variant::Unit(..) => Err(Box::new(TypeError(format!("Unexpected arguments for unit variant"))))?,
+          Variant::Unit(..) => Err(Box::new(TypeError::UnexpectedVariantArguments(span)))?,
           Variant::Struct(_, params) => {
             LValue::StructVariantLit {
+              span,
               ty,
               is_mut: IsMut::No,
               index: *index,
@@ -507,27 +576,27 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
       }
       Str(val) => {
         let ty = Ty::Arr(val.len(), Box::new(self.global.tctx.new_var(Bound::Int)));
-        LValue::StrLit { ty, is_mut: IsMut::No, val: val.clone() }
+        LValue::StrLit { span, ty, is_mut: IsMut::No, val: val.clone() }
       }
       Dot(arg, name) => {
-        self.infer_dot(arg, *name)?
+        self.infer_dot(span, arg, *name)?
       }
       Index(arg, idx) => {
-        self.infer_index(arg, idx)?
+        self.infer_index(span, arg, idx)?
       }
       Ind(arg) => {
-        self.infer_ind(arg)?
+        self.infer_ind(span, arg)?
       }
-      expr => return Err(Box::new(TypeError(format!("Expected lvalue instead of {:?}", expr))))
+      _ => return Err(Box::new(TypeError::NotLValue(span)))
     })
   }
 
   /// Infer the type of a member access expression
-  fn infer_dot(&mut self, arg: &ResolvedExpr, name: RefStr) -> MRes<LValue> {
+  fn infer_dot(&mut self, span: Span, arg: &ResolvedExpr, name: RefStr) -> MRes<LValue> {
     // Infer argument type
     let arg = self.infer_lvalue(arg)?;
 
-    'error: loop {
+    'error: {
       // Find parameter list
       let ty = self.global.tctx.canonical_ty(arg.ty());
 
@@ -547,11 +616,15 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
       // Find parameter
       let (idx, param_ty) = match lin_search(params, &name) {
         Some(val) => val,
-        None => break 'error
+        None => {
+          let suggestion = closest_match(name, params.iter().map(|(name, _)| *name));
+          return Err(Box::new(TypeError::UnknownField(span, ty.clone(), name, suggestion)))
+        }
       };
 
       return if is_stru {
         Ok(LValue::StruDot {
+          span,
           ty: param_ty.clone(),
           is_mut: arg.is_mut(),
           arg: Box::new(arg),
@@ -559,6 +632,7 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
         })
       } else {
         Ok(LValue::UnionDot {
+          span,
           ty: param_ty.clone(),
           is_mut: arg.is_mut(),
           arg: Box::new(arg),
@@ -566,11 +640,12 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
       };
     }
 
-    Err(Box::new(TypeError(format!("Type {:?} has no field named {}", arg.ty(), name))))
+    let ty = self.global.tctx.canonical_ty(arg.ty());
+    Err(Box::new(TypeError::UnknownField(span, ty, name, None)))
   }
 
   /// Infer the type of an array index expression
-  fn infer_index(&mut self, arg: &ResolvedExpr, idx: &ResolvedExpr) -> MRes<LValue> {
+  fn infer_index(&mut self, span: Span, arg: &ResolvedExpr, idx: &ResolvedExpr) -> MRes<LValue> {
     // Infer array type
     let arg = self.infer_lvalue(arg)?;
 
@@ -578,14 +653,15 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
     let ty = self.global.tctx.canonical_ty(arg.ty());
     let elem_ty = match &ty {
       Ty::Arr(_, elem_ty) => &**elem_ty,
-      _ => return Err(Box::new(TypeError(format!("Cannot index type {:?}", arg.ty()))))
+      _ => return Err(Box::new(TypeError::CannotIndex(span, ty.clone())))
     };
 
     // Check index type
     let idx = self.infer_rvalue(idx)?;
-    self.global.tctx.unify(&Ty::Uintn, idx.ty())?;
+    self.global.tctx.unify(idx.span(), &Ty::Uintn, idx.ty())?;
 
     Ok(LValue::Index {
+      span,
       ty: elem_ty.clone(),
       is_mut: arg.is_mut(),
       arg: Box::new(arg),
@@ -594,7 +670,7 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
   }
 
   /// Infer the type of a pointer indirection expression
-  fn infer_ind(&mut self, arg: &ResolvedExpr) -> MRes<LValue> {
+  fn infer_ind(&mut self, span: Span, arg: &ResolvedExpr) -> MRes<LValue> {
     // Infer pointer type
     let arg = self.infer_rvalue(arg)?;
 
@@ -602,11 +678,11 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
     let ty = self.global.tctx.canonical_ty(arg.ty());
     let (is_mut, base_ty) = match &ty {
       Ty::Ptr(is_mut, base_ty) => (*is_mut, &**base_ty),
-      _ => return Err(Box::new(
-        TypeError(format!("Cannot dereference type {:?}", arg.ty()))))
+      _ => return Err(Box::new(TypeError::CannotDereference(span, ty.clone())))
     };
 
     Ok(LValue::Ind {
+      span,
       ty: base_ty.clone(),
       is_mut,
       arg: Box::new(arg),
@@ -615,9 +691,10 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
 
   /// Infer the semantic form of an expression in an rvalue context
   fn infer_rvalue(&mut self, expr: &ResolvedExpr) -> MRes<RValue> {
-    use ResolvedExpr::*;
+    use ResolvedExprKind::*;
 
-    Ok(match expr {
+    let span = expr.span;
+    Ok(match &expr.kind {
       ConstRef(def_id) => {
         let def = self.global.resolved_def(*def_id).unwrap_const();
         self.infer_rvalue(&def.val)?
@@ -627,9 +704,9 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
         let type_args: Vec<Ty> = (0..def.type_params)
           .map(|_| self.global.tctx.new_var(Bound::Any))
           .collect();
-        self.global.inst_func_sig((*def_id, type_args))?
+        self.global.inst_func_sig(span, (*def_id, type_args))?
       },
-      ExternFuncRef(def_id) => self.global.inst_extern_func(*def_id)?,
+      ExternFuncRef(def_id) => self.global.inst_extern_func(span, *def_id)?,
       DataRef(..) |
       ExternDataRef(..) |
       ParamRef(..) |
@@ -647,34 +724,36 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
       Ind(..) => {
         let arg = self.infer_lvalue(expr)?;
         RValue::Load {
+          span,
           ty: arg.ty().clone(),
           arg: Box::new(arg),
         }
       }
       CStr(val) => {
-        RValue::CStr { ty: Ty::Ptr(IsMut::No, Box::new(Ty::Int8)), val: val.clone() }
+        RValue::CStr { span, ty: Ty::Ptr(IsMut::No, Box::new(Ty::Int8)), val: val.clone() }
       }
       Nil => {
-        RValue::Nil { ty: Ty::Ptr(IsMut::Yes, Box::new(self.global.tctx.new_var(Bound::Any))) }
+        RValue::Nil { span, ty: Ty::Ptr(IsMut::Yes, Box::new(self.global.tctx.new_var(Bound::Any))) }
       }
       Bool(val) => {
-        RValue::Bool { ty: Ty::Bool, val: *val }
+        RValue::Bool { span, ty: Ty::Bool, val: *val }
       }
       Int(val) => {
-        RValue::Int { ty: self.global.tctx.new_var(Bound::Int), val: *val }
+        RValue::Int { span, ty: self.global.tctx.new_var(Bound::Int), val: *val }
       }
       Flt(val) => {
-        RValue::Flt { ty: self.global.tctx.new_var(Bound::Flt), val: *val }
+        RValue::Flt { span, ty: self.global.tctx.new_var(Bound::Flt), val: *val }
       }
       Unit => {
-        RValue::Unit { ty: Ty::Unit }
+        RValue::Unit { span, ty: Ty::Unit }
       }
       Call(called, args) => {
-        self.infer_call(called, args)?
+        self.infer_call(span, called, args)?
       }
       Adr(arg) => {
         let arg = self.infer_lvalue(arg)?;
         RValue::Adr {
+          span,
           ty: Ty::Ptr(arg.is_mut(), Box::new(arg.ty().clone())),
           arg: Box::new(arg),
         }
@@ -682,46 +761,54 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
       Un(op, arg) => {
         let arg = self.infer_rvalue(arg)?;
         RValue::Un {
-          ty: self.infer_un(*op, arg.ty())?,
+          span,
+          ty: self.infer_un(span, *op, arg.ty())?,
           op: *op,
           arg: Box::new(arg),
         }
       }
       LNot(arg) => {
         let arg = self.infer_rvalue(arg)?;
-        self.global.tctx.unify(&Ty::Bool, arg.ty())?;
-        RValue::LNot { ty: Ty::Bool, arg: Box::new(arg) }
+        self.global.tctx.unify(arg.span(), &Ty::Bool, arg.ty())?;
+        RValue::LNot { span, ty: Ty::Bool, arg: Box::new(arg) }
       }
       Cast(arg, ty) => {
         let arg = self.infer_rvalue(arg)?;
         let ty = self.infer_ty(ty)?;
         // FIXME: actually check if the type conversion is valid or not
-        RValue::Cast { ty, arg: Box::new(arg) }
+        RValue::Cast { span, ty, arg: Box::new(arg) }
       }
       Bin(op, lhs, rhs) => {
         let lhs = self.infer_rvalue(lhs)?;
         let rhs = self.infer_rvalue(rhs)?;
-        let ty = self.infer_bin(*op, lhs.ty(), rhs.ty())?;
-        RValue::Bin { ty, op: *op, lhs: Box::new(lhs), rhs: Box::new(rhs) }
+        let ty = self.infer_bin(span, *op, lhs.ty(), rhs.ty())?;
+        RValue::Bin { span, ty, op: *op, lhs: Box::new(lhs), rhs: Box::new(rhs) }
       }
       LAnd(lhs, rhs) => {
         let lhs = self.infer_rvalue(lhs)?;
         let rhs = self.infer_rvalue(rhs)?;
-        self.global.tctx.unify(&Ty::Bool, lhs.ty())?;
-        self.global.tctx.unify(&Ty::Bool, rhs.ty())?;
-        RValue::LAnd { ty: Ty::Bool, lhs: Box::new(lhs), rhs: Box::new(rhs) }
+        self.global.tctx.unify(lhs.span(), &Ty::Bool, lhs.ty())?;
+        self.global.tctx.unify(rhs.span(), &Ty::Bool, rhs.ty())?;
+        RValue::LAnd { span, ty: Ty::Bool, lhs: Box::new(lhs), rhs: Box::new(rhs) }
       }
       LOr(lhs, rhs) => {
         let lhs = self.infer_rvalue(lhs)?;
         let rhs = self.infer_rvalue(rhs)?;
-        self.global.tctx.unify(&Ty::Bool, lhs.ty())?;
-        self.global.tctx.unify(&Ty::Bool, rhs.ty())?;
-        RValue::LOr { ty: Ty::Bool, lhs: Box::new(lhs), rhs: Box::new(rhs) }
+        self.global.tctx.unify(lhs.span(), &Ty::Bool, lhs.ty())?;
+        self.global.tctx.unify(rhs.span(), &Ty::Bool, rhs.ty())?;
+        RValue::LOr { span, ty: Ty::Bool, lhs: Box::new(lhs), rhs: Box::new(rhs) }
       }
       Block(parsed_body) => {
         let mut body = vec![];
         for expr in parsed_body {
-          body.push(self.infer_rvalue(expr)?);
+          // Report errors, and carry on with the next expression
+          body.push(match self.infer_rvalue(expr) {
+            Ok(rvalue) => rvalue,
+            Err(error) => {
+              self.global.errors.push(error);
+              RValue::Error { span: expr.span, ty: Ty::Error }
+            }
+          });
         }
 
         let ty = if let Some(last) = body.last() {
@@ -730,97 +817,99 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
           Ty::Unit
         };
 
-        RValue::Block { ty, body }
+        RValue::Block { span, ty, body }
       }
       As(lhs, rhs) => {
         // Infer argument types
         let lhs = self.infer_lvalue(lhs)?;
         let rhs = self.infer_rvalue(rhs)?;
-        self.global.tctx.unify(lhs.ty(), rhs.ty())?;
+        self.global.tctx.unify(rhs.span(), lhs.ty(), rhs.ty())?;
 
         // Make sure lhs is mutable
         match lhs.is_mut() {
           IsMut::Yes => (),
-          _ => return Err(Box::new(
-            TypeError(format!("Cannot assign to immutable location {:?}", lhs)))),
+          _ => return Err(Box::new(TypeError::AssignToImmutable(span))),
         };
 
-        RValue::As { ty: Ty::Unit, lhs: Box::new(lhs), rhs: Box::new(rhs) }
+        RValue::As { span, ty: Ty::Unit, lhs: Box::new(lhs), rhs: Box::new(rhs) }
       }
       Rmw(op, lhs, rhs) => {
         // Infer and check argument types
         let lhs = self.infer_lvalue(lhs)?;
         let rhs = self.infer_rvalue(rhs)?;
-        self.infer_bin(*op, lhs.ty(), rhs.ty())?;
+        self.infer_bin(span, *op, lhs.ty(), rhs.ty())?;
 
         // Make sure lhs is mutable
         match lhs.is_mut() {
           IsMut::Yes => (),
-          _ => return Err(Box::new(
-            TypeError(format!("Cannot assign to immutable location {:?}", lhs)))),
+          _ => return Err(Box::new(TypeError::AssignToImmutable(span))),
         };
 
-        RValue::Rmw { ty: Ty::Unit, op: *op, lhs: Box::new(lhs), rhs: Box::new(rhs) }
+        RValue::Rmw { span, ty: Ty::Unit, op: *op, lhs: Box::new(lhs), rhs: Box::new(rhs) }
       }
       Continue => {
         // Can only have continue inside a loop
         match self.loop_ty.last() {
           Some(..) => (),
-          None => return Err(Box::new(
-            TypeError(format!("Continue outside loop")))),
+          None => return Err(Box::new(TypeError::ContinueOutsideLoop(span))),
         };
 
-        RValue::Continue { ty: self.global.tctx.new_var(Bound::Any) }
+        RValue::Continue { span, ty: self.global.tctx.new_var(Bound::Any) }
       }
       Break(arg) => {
-        let arg = self.infer_rvalue(&*arg)?;
+        let arg = self.infer_rvalue(arg)?;
 
         // Can only have break inside a loop
         let loop_ty = match self.loop_ty.last() {
           Some(loop_ty) => loop_ty.clone(),
-          None => return Err(Box::new(
-            TypeError(format!("Break outside loop")))),
+          None => return Err(Box::new(TypeError::BreakOutsideLoop(span))),
         };
 
         // Unify function return type with the returned value's type
-        self.global.tctx.unify(&loop_ty, arg.ty())?;
+        self.global.tctx.unify(arg.span(), &loop_ty, arg.ty())?;
 
-        RValue::Break { ty: self.global.tctx.new_var(Bound::Any), arg: Box::new(arg) }
+        RValue::Break { span, ty: self.global.tctx.new_var(Bound::Any), arg: Box::new(arg) }
       }
       Return(arg) => {
-        let arg = self.infer_rvalue(&*arg)?;
+        let arg = self.infer_rvalue(arg)?;
 
         // Can only have return inside a function
-        let ret_ty = match self.ret_ty.as_ref() {
+        let (ret_ty, annotation) = match self.ret_ty.as_ref() {
           Some(ret_ty) => ret_ty.clone(),
-          None => return Err(Box::new(TypeError(format!("Return outside function")))),
+          None => return Err(Box::new(TypeError::ReturnOutsideFunction(span))),
         };
 
         // Unify function return type with the returned value's type
-        self.global.tctx.unify(&ret_ty, arg.ty())?;
+        self.global.tctx.unify(arg.span(), &ret_ty, arg.ty())
+          .map_err(|error| error.annotated(annotation))?;
 
-        RValue::Return { ty: self.global.tctx.new_var(Bound::Any), arg: Box::new(arg) }
+        RValue::Return { span, ty: self.global.tctx.new_var(Bound::Any), arg: Box::new(arg) }
       }
       Let(index, init) => {
         let init = if let Some(init) = init {
           let init = self.infer_rvalue(init)?;
-          self.global.tctx.unify(&self.locals[*index].1, init.ty())?;
+          self.global.tctx.unify(init.span(), &self.locals[*index].1, init.ty())
+            .map_err(|error| match self.local_annotations[*index] {
+              Some(annotation) => error.annotated(annotation),
+              None => error
+            })?;
           Some(Box::new(init))
         } else {
           None
         };
 
-        RValue::Let { ty: Ty::Unit, index: *index, init }
+        RValue::Let { span, ty: Ty::Unit, index: *index, init }
       }
       If(cond, tbody, ebody) => {
         let cond = self.infer_rvalue(cond)?;
-        self.global.tctx.unify(&Ty::Bool, cond.ty())?;
+        self.global.tctx.unify(cond.span(), &Ty::Bool, cond.ty())?;
 
         let tbody = self.infer_rvalue(tbody)?;
         let ebody = self.infer_rvalue(ebody)?;
-        self.global.tctx.unify(tbody.ty(), ebody.ty())?;
+        self.global.tctx.unify(ebody.span(), tbody.ty(), ebody.ty())?;
 
         RValue::If {
+          span,
           ty: tbody.ty().clone(),
           cond: Box::new(cond),
           tbody: Box::new(tbody),
@@ -829,13 +918,14 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
       }
       While(cond, body) => {
         let cond = self.infer_rvalue(cond)?;
-        self.global.tctx.unify(&Ty::Bool, cond.ty())?;
+        self.global.tctx.unify(cond.span(), &Ty::Bool, cond.ty())?;
 
         self.loop_ty.push(Ty::Unit);
         let body = self.infer_rvalue(body)?;
         let ty = self.loop_ty.pop().unwrap();
 
         RValue::While {
+          span,
           ty,
           cond: Box::new(cond),
           body: Box::new(body),
@@ -847,17 +937,19 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
         let ty = self.loop_ty.pop().unwrap();
 
         RValue::Loop {
+          span,
           ty,
           body: Box::new(body),
         }
       }
       Match(cond, cases) => {
-        self.infer_match(cond, cases)?
+        self.infer_match(span, cond, cases)?
       }
+      Error => RValue::Error { span, ty: Ty::Error }
     })
   }
 
-  fn infer_call(&mut self, called: &ResolvedExpr, args: &Vec<(RefStr, ResolvedExpr)>) -> MRes<RValue> {
+  fn infer_call(&mut self, span: Span, called: &ResolvedExpr, args: &[(RefStr, ResolvedExpr)]) -> MRes<RValue> {
     // Infer function type
     let called_expr = self.infer_rvalue(called)?;
 
@@ -866,20 +958,21 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
 
     let (params, va, ret_ty) = match &called_ty {
       Ty::Func(params, va, ret_ty) => (params, *va, &**ret_ty),
-      _ => return Err(Box::new(TypeError(format!("Cannot call type {:?}", called_expr.ty()))))
+      _ => return Err(Box::new(TypeError::CannotCall(span, called_ty.clone())))
     };
 
     // Validate argument count
     if args.len() < params.len() {
-      return Err(Box::new(TypeError(format!("Not enough arguments for {:?}", called_expr.ty()))));
+      return Err(Box::new(TypeError::NotEnoughArguments(span, called_ty.clone())));
     }
-    if va == false && args.len() > params.len() {
-      return Err(Box::new(TypeError(format!("Too many arguments for {:?}", called_expr.ty()))));
+    if !va && args.len() > params.len() {
+      return Err(Box::new(TypeError::TooManyArguments(span, called_ty.clone())));
     }
 
     let args = self.infer_args(params, args)?;
 
     Ok(RValue::Call {
+      span,
       ty: ret_ty.clone(),
       func: Box::new(called_expr),
       args,
@@ -896,9 +989,9 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
       // If there is a corresponding parameter name and type, check it
       if let Some((param_name, param_ty)) = params_iter.next() {
         if *arg_name != RefStr::new("") && arg_name != param_name {
-          return Err(Box::new(TypeError(format!("Incorrect argument label {}", arg_name))));
+          return Err(Box::new(TypeError::IncorrectArgumentLabel(arg_val.span(), *arg_name)));
         }
-        self.global.tctx.unify(arg_val.ty(), param_ty)?;
+        self.global.tctx.unify(arg_val.span(), arg_val.ty(), param_ty)?;
       }
       // Append checked argument
       nargs.push(arg_val);
@@ -907,57 +1000,57 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
     Ok(nargs)
   }
 
-  fn infer_un(&mut self, op: UnOp, arg: &Ty) -> MRes<Ty> {
+  fn infer_un(&mut self, span: Span, op: UnOp, arg: &Ty) -> Result<Ty, CannotUnifyError> {
     // Check argument type
     match op {
       UnOp::UPlus | UnOp::UMinus => {
-        self.global.tctx.bound(arg, &Bound::Num)
+        self.global.tctx.bound(span, arg, &Bound::Num)
       }
       UnOp::Not => {
-        self.global.tctx.bound(arg, &Bound::Int)
+        self.global.tctx.bound(span, arg, &Bound::Int)
       }
     }
   }
 
-  fn infer_bin(&mut self, op: BinOp, lhs: &Ty, rhs: &Ty) -> MRes<Ty> {
+  fn infer_bin(&mut self, span: Span, op: BinOp, lhs: &Ty, rhs: &Ty) -> Result<Ty, CannotUnifyError> {
     // Check argument types and infer result type
     match op {
       // Both arguments must have matching numeric types
       // Result has the same type as the arguments
       BinOp::Mul | BinOp::Div | BinOp::Add | BinOp::Sub => {
-        self.global.tctx.bound(lhs, &Bound::Num)?;
-        self.global.tctx.unify(lhs, rhs)
+        self.global.tctx.bound(span, lhs, &Bound::Num)?;
+        self.global.tctx.unify(span, lhs, rhs)
       }
 
       // Both arguments must have matching integer types
       // Result has the same type as the arguments
       BinOp::Mod | BinOp::And | BinOp::Xor | BinOp::Or => {
-        self.global.tctx.bound(lhs, &Bound::Int)?;
-        self.global.tctx.unify(lhs, rhs)
+        self.global.tctx.bound(span, lhs, &Bound::Int)?;
+        self.global.tctx.unify(span, lhs, rhs)
       }
 
       // Both arguments must have integer types
       // Result has the left argument's type
       BinOp::Lsh | BinOp::Rsh => {
-        self.global.tctx.bound(rhs, &Bound::Int)?;
-        self.global.tctx.bound(lhs, &Bound::Int)
+        self.global.tctx.bound(span, rhs, &Bound::Int)?;
+        self.global.tctx.bound(span, lhs, &Bound::Int)
       }
 
       BinOp::Eq | BinOp::Ne => {
-        self.global.tctx.bound(lhs, &Bound::Eq)?;
-        self.global.tctx.unify(lhs, rhs)?;
+        self.global.tctx.bound(span, lhs, &Bound::Eq)?;
+        self.global.tctx.unify(span, lhs, rhs)?;
         Ok(Ty::Bool)
       }
 
       BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge => {
-        self.global.tctx.bound(lhs, &Bound::Num)?;
-        self.global.tctx.unify(lhs, rhs)?;
+        self.global.tctx.bound(span, lhs, &Bound::Num)?;
+        self.global.tctx.unify(span, lhs, rhs)?;
         Ok(Ty::Bool)
       }
     }
   }
 
-  fn infer_match(&mut self, cond: &ResolvedExpr, cases: &[(Option<usize>, RefStr, ResolvedExpr)]) -> MRes<RValue> {
+  fn infer_match(&mut self, span: Span, cond: &ResolvedExpr, cases: &[(Option<usize>, RefStr, ResolvedExpr)]) -> MRes<RValue> {
     // FIXME: struct variant binding semantics on rvalue enums are hacky at best :(
     //
     // Enums are **always** lvalues at the LLVM level (even when semantically they were rvalues).
@@ -986,16 +1079,30 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
         let (_, variants) = self.global.find_inst(&id).unwrap_enum();
         variants.clone()
       },
-      _ => Err(Box::new(TypeError(format!("Cannot match on non-enum type {:?}", cond.ty()))))?
+      ty => Err(Box::new(TypeError::MatchOnNonEnum(cond.span(), ty)))?
     };
 
+    // Make sure every case refers to an existing variant
+    let variant_names: Vec<RefStr> = variants
+      .iter()
+      .map(|variant| match variant {
+        Variant::Unit(name) | Variant::Struct(name, ..) => *name
+      })
+      .collect();
+    for (_, variant, _) in cases.iter() {
+      if !variant_names.contains(variant) {
+        let suggestion = closest_match(*variant, variant_names.iter().cloned());
+        Err(Box::new(TypeError::UnknownVariant(span, *variant, suggestion)))?
+      }
+    }
+
     // Create lookup table for cases
     let mut case_lookup: HashMap<RefStr, (Option<usize>, &ResolvedExpr)> = HashMap::new();
 
     for (binding, variant, val) in cases.iter() {
       // Check for duplicate case
       if case_lookup.contains_key(variant) {
-        Err(Box::new(TypeError(format!("Duplicate match case"))))?
+        Err(Box::new(TypeError::DuplicateMatchCase(val.span)))?
       }
       // Insert case
       case_lookup.insert(*variant, (*binding, val));
@@ -1012,7 +1119,7 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
 
       let (binding, val) = case_lookup
         .remove(&name)
-        .ok_or_else(|| TypeError(format!("Missing match case for variant {}", name)))?;
+        .ok_or(TypeError::NonExhaustiveMatch(span, name))?;
 
       if let Some(binding) = binding {
         assert_eq!(self.bindings.len(), binding);
@@ -1022,23 +1129,18 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
       inferred_cases.push((binding, self.infer_rvalue(val)?));
     }
 
-    // Make sure there are no cases left over
-    if case_lookup.len() > 0 {
-      Err(Box::new(TypeError(format!("Match case for unknown variant"))))?
-    }
-
     // Unify case types
-    let ty = if inferred_cases.len() > 0 {
+    let ty = if !inferred_cases.is_empty() {
       inferred_cases[1..]
         .iter()
-        .map(|(_, val)| val.ty())
         .try_fold(inferred_cases[0].1.ty().clone(),
-                  |a, b| self.global.tctx.unify(&a, b))?
+                  |a, (_, val)| self.global.tctx.unify(val.span(), &a, val.ty()))?
     } else {
       Ty::Unit
     };
 
     Ok(RValue::Match {
+      span,
       ty,
       cond: Box::new(cond),
       cases: inferred_cases
@@ -1048,11 +1150,116 @@ impl<'global, 'repo, 'tctx> DefCtx<'global, 'repo, 'tctx> {
 
 /// Errors
 #[derive(Debug)]
-struct TypeError(String);
+pub(crate) enum TypeError {
+  NonExhaustiveMatch(Span, RefStr),
+  // Field that does not exist, with a similarly named one if found
+  UnknownField(Span, Ty, RefStr, Option<RefStr>),
+  // Match case for a variant that does not exist, with a similarly named one if found
+  UnknownVariant(Span, RefStr, Option<RefStr>),
+  DuplicateMatchCase(Span),
+  MatchOnNonEnum(Span, Ty),
+  // Use with the wrong number of type arguments, and the definition used
+  WrongTypeArgCount(Span, Span),
+  CannotCall(Span, Ty),
+  NotEnoughArguments(Span, Ty),
+  TooManyArguments(Span, Ty),
+  IncorrectArgumentLabel(Span, RefStr),
+  CannotIndex(Span, Ty),
+  CannotDereference(Span, Ty),
+  NotLValue(Span),
+  AssignToImmutable(Span),
+  ContinueOutsideLoop(Span),
+  BreakOutsideLoop(Span),
+  ReturnOutsideFunction(Span),
+  MissingVariantArguments(Span),
+  UnexpectedVariantArguments(Span)
+}
+
+impl TypeError {
+  pub fn code(&self) -> &'static str {
+    use TypeError::*;
+    match self {
+      NonExhaustiveMatch(..) => "E0203",
+      UnknownField(..) => "E0204",
+      UnknownVariant(..) => "E0206",
+      DuplicateMatchCase(..) => "E0207",
+      MatchOnNonEnum(..) => "E0208",
+      WrongTypeArgCount(..) => "E0209",
+      CannotCall(..) => "E0210",
+      NotEnoughArguments(..) => "E0211",
+      TooManyArguments(..) => "E0212",
+      IncorrectArgumentLabel(..) => "E0213",
+      CannotIndex(..) => "E0214",
+      CannotDereference(..) => "E0215",
+      NotLValue(..) => "E0216",
+      AssignToImmutable(..) => "E0217",
+      ContinueOutsideLoop(..) => "E0218",
+      BreakOutsideLoop(..) => "E0219",
+      ReturnOutsideFunction(..) => "E0220",
+      MissingVariantArguments(..) => "E0221",
+      UnexpectedVariantArguments(..) => "E0222"
+    }
+  }
+
+  pub fn span(&self) -> Span {
+    use TypeError::*;
+    match self {
+      NonExhaustiveMatch(span, ..) |
+      UnknownField(span, ..) |
+      UnknownVariant(span, ..) |
+      DuplicateMatchCase(span) |
+      MatchOnNonEnum(span, ..) |
+      WrongTypeArgCount(span, ..) |
+      CannotCall(span, ..) |
+      NotEnoughArguments(span, ..) |
+      TooManyArguments(span, ..) |
+      IncorrectArgumentLabel(span, ..) |
+      CannotIndex(span, ..) |
+      CannotDereference(span, ..) |
+      NotLValue(span) |
+      AssignToImmutable(span) |
+      ContinueOutsideLoop(span) |
+      BreakOutsideLoop(span) |
+      ReturnOutsideFunction(span) |
+      MissingVariantArguments(span) |
+      UnexpectedVariantArguments(span) => *span
+    }
+  }
+
+  /// Similarly named member, if the error is about one that does not exist
+  pub fn suggestion(&self) -> Option<RefStr> {
+    match self {
+      TypeError::UnknownField(_, _, _, suggestion) |
+      TypeError::UnknownVariant(_, _, suggestion) => *suggestion,
+      _ => None
+    }
+  }
+}
 
 impl fmt::Display for TypeError {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    write!(f, "{}", self.0)
+    use TypeError::*;
+    match self {
+      NonExhaustiveMatch(_, name) => write!(f, "Missing match case for variant {}", name),
+      UnknownField(_, ty, name, _) => write!(f, "Type {:?} has no field named {}", ty, name),
+      UnknownVariant(_, name, _) => write!(f, "Match case for unknown variant {}", name),
+      DuplicateMatchCase(..) => write!(f, "Duplicate match case"),
+      MatchOnNonEnum(_, ty) => write!(f, "Cannot match on non-enum type {:?}", ty),
+      WrongTypeArgCount(..) => write!(f, "Incorrect number of type parameters"),
+      CannotCall(_, ty) => write!(f, "Cannot call type {:?}", ty),
+      NotEnoughArguments(_, ty) => write!(f, "Not enough arguments for {:?}", ty),
+      TooManyArguments(_, ty) => write!(f, "Too many arguments for {:?}", ty),
+      IncorrectArgumentLabel(_, name) => write!(f, "Incorrect argument label {}", name),
+      CannotIndex(_, ty) => write!(f, "Cannot index type {:?}", ty),
+      CannotDereference(_, ty) => write!(f, "Cannot dereference type {:?}", ty),
+      NotLValue(..) => write!(f, "Expected lvalue"),
+      AssignToImmutable(..) => write!(f, "Cannot assign to immutable location"),
+      ContinueOutsideLoop(..) => write!(f, "Continue outside loop"),
+      BreakOutsideLoop(..) => write!(f, "Break outside loop"),
+      ReturnOutsideFunction(..) => write!(f, "Return outside function"),
+      MissingVariantArguments(..) => write!(f, "Expected arguments for struct variant"),
+      UnexpectedVariantArguments(..) => write!(f, "Unexpected arguments for unit variant")
+    }
   }
 }
 
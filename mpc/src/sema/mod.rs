@@ -10,7 +10,9 @@
 // operate on the same intermediate representation.
 //
 
-use crate::parse::{self, IsMut, UnOp, BinOp, DefId};
+use crate::mangle;
+use crate::parse::{self, IsExport, IsMut, UnOp, BinOp, DefId};
+use crate::source::Span;
 use crate::util::*;
 use std::collections::HashMap;
 use std::error;
@@ -22,15 +24,28 @@ mod tctx;
 
 pub use consteval::*;
 use infer::*;
+pub(crate) use infer::TypeError;
 pub use tctx::*;
 
 pub fn analyze(repo: &parse::Repository) -> MRes<Collection> {
   let mut tctx = TVarCtx::new();
-  let insts = infer(repo, &mut tctx)?;
-  if let Some(_) = option_env!("MPC_SPEW") {
-    eprintln!("{:#?}", insts);
-    eprintln!("{:#?}", tctx);
+  let mut insts = infer(repo, &mut tctx)?;
+
+  // Give every function and data instance a unique symbol name,
+  // apart from those that need to be visible to C code
+  for (id, inst) in insts.iter_mut() {
+    match inst {
+      Inst::Func { name, is_export, .. } if repo.module_path(id.0).is_empty() && name.borrow_rs() == "main" => {
+        *is_export = IsExport::Yes
+      }
+      Inst::Func { name, symbol, is_export: IsExport::No, .. } |
+      Inst::Data { name, symbol, is_export: IsExport::No, .. } => {
+        *symbol = RefStr::new(&mangle::mangle(repo, id, *name))
+      }
+      _ => ()
+    }
   }
+
   Ok(Collection {
     tctx,
     insts
@@ -59,15 +74,21 @@ pub enum Inst {
     variants: Option<Vec<Variant>>
   },
   Func {
+    span: Span,
     name: RefStr,
+    symbol: RefStr,
+    is_export: IsExport,
     ty: Ty,
-    params: Vec<(IsMut, Ty)>,
-    locals: Vec<(IsMut, Ty)>,
-    bindings: Vec<(IsMut, Ty)>,
+    params: Vec<(Span, RefStr, IsMut, Ty)>,
+    locals: Vec<(Span, RefStr, IsMut, Ty)>,
+    bindings: Vec<(Span, RefStr, IsMut, Ty)>,
     body: Option<RValue>
   },
   Data {
     name: RefStr,
+    symbol: RefStr,
+    is_export: IsExport,
+    #[allow(dead_code)]
     ty: Ty,
     is_mut: IsMut,
     init: ConstVal
@@ -79,6 +100,7 @@ pub enum Inst {
   ExternData {
     name: RefStr,
     ty: Ty,
+    #[allow(dead_code)]
     is_mut: IsMut
   }
 }
@@ -141,7 +163,9 @@ pub enum Ty {
   Unit,
   Tuple(Vec<(RefStr, Ty)>),
   // Type variable
-  Var(usize)
+  Var(usize),
+  // Type of an expression that failed to type check
+  Error
 }
 
 impl Ty {
@@ -175,7 +199,7 @@ impl fmt::Debug for Ty {
       UnionRef(name, type_args) |
       EnumRef(name, type_args) => {
         write!(f, "{}", name)?;
-        if type_args.1.len() > 0 {
+        if !type_args.1.is_empty() {
           write_comma_separated(f, type_args.1.iter(), |f, ty| ty.fmt(f))
         } else {
           Ok(())
@@ -196,7 +220,8 @@ impl fmt::Debug for Ty {
         write_comma_separated(f,
                               params.iter(), |f, (name, ty)| write!(f, "{}: {:?}", name, ty))
       }
-      Var(idx) => write!(f, "'{}", idx)
+      Var(idx) => write!(f, "'{}", idx),
+      Error => write!(f, "<error>")
     }
   }
 }
@@ -204,55 +229,76 @@ impl fmt::Debug for Ty {
 /// Expressions
 #[derive(Debug)]
 pub enum LValue {
-  DataRef { ty: Ty, is_mut: IsMut, id: DefId },
-  ParamRef { ty: Ty, is_mut: IsMut, index: usize },
-  LetRef { ty: Ty, is_mut: IsMut, index: usize },
-  BindingRef { ty: Ty, is_mut: IsMut, index: usize },
-  StrLit { ty: Ty, is_mut: IsMut, val: Vec<u8> },
-  TupleLit { ty: Ty, is_mut: IsMut, fields: Vec<RValue> },
-  ArrayLit { ty: Ty, is_mut: IsMut, elements: Vec<RValue> },
-  StructLit { ty: Ty, is_mut: IsMut, fields: Vec<RValue> },
-  UnionLit { ty: Ty, is_mut: IsMut, field: RValue },
-  UnitVariantLit { ty: Ty, is_mut: IsMut, index: usize },
-  StructVariantLit { ty: Ty, is_mut: IsMut, index: usize, fields: Vec<RValue> },
-  StruDot { ty: Ty, is_mut: IsMut, arg: Box<LValue>, idx: usize },
-  UnionDot { ty: Ty, is_mut: IsMut, arg: Box<LValue> },
-  Index { ty: Ty, is_mut: IsMut, arg: Box<LValue>, idx: Box<RValue> },
-  Ind { ty: Ty, is_mut: IsMut, arg: Box<RValue> },
+  DataRef { span: Span, ty: Ty, is_mut: IsMut, id: DefId },
+  ParamRef { span: Span, ty: Ty, is_mut: IsMut, index: usize },
+  LetRef { span: Span, ty: Ty, is_mut: IsMut, index: usize },
+  BindingRef { span: Span, ty: Ty, is_mut: IsMut, index: usize },
+  StrLit { span: Span, ty: Ty, is_mut: IsMut, val: Vec<u8> },
+  TupleLit { span: Span, ty: Ty, is_mut: IsMut, fields: Vec<RValue> },
+  ArrayLit { span: Span, ty: Ty, is_mut: IsMut, elements: Vec<RValue> },
+  StructLit { span: Span, ty: Ty, is_mut: IsMut, fields: Vec<RValue> },
+  UnionLit { span: Span, ty: Ty, is_mut: IsMut, field: RValue },
+  UnitVariantLit { span: Span, ty: Ty, is_mut: IsMut, index: usize },
+  StructVariantLit { span: Span, ty: Ty, is_mut: IsMut, index: usize, fields: Vec<RValue> },
+  StruDot { span: Span, ty: Ty, is_mut: IsMut, arg: Box<LValue>, idx: usize },
+  UnionDot { span: Span, ty: Ty, is_mut: IsMut, arg: Box<LValue> },
+  Index { span: Span, ty: Ty, is_mut: IsMut, arg: Box<LValue>, idx: Box<RValue> },
+  Ind { span: Span, ty: Ty, is_mut: IsMut, arg: Box<RValue> },
 }
 
 #[derive(Debug)]
 pub enum RValue {
-  Unit { ty: Ty },
-  FuncRef { ty: Ty, id: (DefId, Vec<Ty>) },
-  CStr { ty: Ty, val: Vec<u8> },
-  Load { ty: Ty, arg: Box<LValue> },
-  Nil { ty: Ty },
-  Bool { ty: Ty, val: bool },
-  Int { ty: Ty, val: usize },
-  Flt { ty: Ty, val: f64 },
-  Call { ty: Ty, func: Box<RValue>, args: Vec<RValue> },
-  Adr { ty: Ty, arg: Box<LValue> },
-  Un { ty: Ty, op: UnOp, arg: Box<RValue> },
-  LNot { ty: Ty, arg: Box<RValue> },
-  Cast { ty: Ty, arg: Box<RValue> },
-  Bin { ty: Ty, op: BinOp, lhs: Box<RValue>, rhs: Box<RValue> },
-  LAnd { ty: Ty, lhs: Box<RValue>, rhs: Box<RValue> },
-  LOr { ty: Ty, lhs: Box<RValue>, rhs: Box<RValue> },
-  Block { ty: Ty, body: Vec<RValue> },
-  As { ty: Ty, lhs: Box<LValue>, rhs: Box<RValue> },
-  Rmw { ty: Ty, op: BinOp, lhs: Box<LValue>, rhs: Box<RValue> },
-  Continue { ty: Ty },
-  Break { ty: Ty, arg: Box<RValue> },
-  Return { ty: Ty, arg: Box<RValue> },
-  Let { ty: Ty, index: usize, init: Option<Box<RValue>> },
-  If { ty: Ty, cond: Box<RValue>, tbody: Box<RValue>, ebody: Box<RValue> },
-  While { ty: Ty, cond: Box<RValue>, body: Box<RValue> },
-  Loop { ty: Ty, body: Box<RValue> },
-  Match { ty: Ty, cond: Box<RValue>, cases: Vec<(Option<usize>, RValue)> }
+  Unit { span: Span, ty: Ty },
+  FuncRef { span: Span, ty: Ty, id: (DefId, Vec<Ty>) },
+  CStr { span: Span, ty: Ty, val: Vec<u8> },
+  Load { span: Span, ty: Ty, arg: Box<LValue> },
+  Nil { span: Span, ty: Ty },
+  Bool { span: Span, ty: Ty, val: bool },
+  Int { span: Span, ty: Ty, val: usize },
+  Flt { span: Span, ty: Ty, val: f64 },
+  Call { span: Span, ty: Ty, func: Box<RValue>, args: Vec<RValue> },
+  Adr { span: Span, ty: Ty, arg: Box<LValue> },
+  Un { span: Span, ty: Ty, op: UnOp, arg: Box<RValue> },
+  LNot { span: Span, ty: Ty, arg: Box<RValue> },
+  Cast { span: Span, ty: Ty, arg: Box<RValue> },
+  Bin { span: Span, ty: Ty, op: BinOp, lhs: Box<RValue>, rhs: Box<RValue> },
+  LAnd { span: Span, ty: Ty, lhs: Box<RValue>, rhs: Box<RValue> },
+  LOr { span: Span, ty: Ty, lhs: Box<RValue>, rhs: Box<RValue> },
+  Block { span: Span, ty: Ty, body: Vec<RValue> },
+  As { span: Span, ty: Ty, lhs: Box<LValue>, rhs: Box<RValue> },
+  Rmw { span: Span, ty: Ty, op: BinOp, lhs: Box<LValue>, rhs: Box<RValue> },
+  Continue { span: Span, ty: Ty },
+  Break { span: Span, ty: Ty, arg: Box<RValue> },
+  Return { span: Span, ty: Ty, arg: Box<RValue> },
+  Let { span: Span, ty: Ty, index: usize, init: Option<Box<RValue>> },
+  If { span: Span, ty: Ty, cond: Box<RValue>, tbody: Box<RValue>, ebody: Box<RValue> },
+  While { span: Span, ty: Ty, cond: Box<RValue>, body: Box<RValue> },
+  Loop { span: Span, ty: Ty, body: Box<RValue> },
+  Match { span: Span, ty: Ty, cond: Box<RValue>, cases: Vec<(Option<usize>, RValue)> },
+  Error { span: Span, ty: Ty }
 }
 
 impl LValue {
+  pub fn span(&self) -> Span {
+    match self {
+      LValue::DataRef { span, .. } => *span,
+      LValue::ParamRef { span, .. } => *span,
+      LValue::LetRef { span, .. } => *span,
+      LValue::BindingRef { span, .. } => *span,
+      LValue::StrLit { span, .. } => *span,
+      LValue::TupleLit { span, .. } => *span,
+      LValue::ArrayLit { span, .. } => *span,
+      LValue::StructLit { span, .. } => *span,
+      LValue::UnionLit { span, .. } => *span,
+      LValue::UnitVariantLit { span, .. } => *span,
+      LValue::StructVariantLit { span, .. } => *span,
+      LValue::StruDot { span, .. } => *span,
+      LValue::UnionDot { span, .. } => *span,
+      LValue::Index { span, .. } => *span,
+      LValue::Ind { span, .. } => *span,
+    }
+  }
+
   pub fn ty(&self) -> &Ty {
     match self {
       LValue::DataRef { ty, .. } => ty,
@@ -295,6 +341,39 @@ impl LValue {
 }
 
 impl RValue {
+  pub fn span(&self) -> Span {
+    match self {
+      RValue::Unit { span, .. } => *span,
+      RValue::FuncRef { span, .. } => *span,
+      RValue::CStr { span, .. } => *span,
+      RValue::Load { span, .. } => *span,
+      RValue::Nil { span, .. } => *span,
+      RValue::Bool { span, .. } => *span,
+      RValue::Int { span, .. } => *span,
+      RValue::Flt { span, .. } => *span,
+      RValue::Call { span, .. } => *span,
+      RValue::Adr { span, .. } => *span,
+      RValue::Un { span, .. } => *span,
+      RValue::LNot { span, .. } => *span,
+      RValue::Cast { span, .. } => *span,
+      RValue::Bin { span, .. } => *span,
+      RValue::LAnd { span, .. } => *span,
+      RValue::LOr { span, .. } => *span,
+      RValue::Block { span, .. } => *span,
+      RValue::As { span, .. } => *span,
+      RValue::Rmw { span, .. } => *span,
+      RValue::Continue { span, .. } => *span,
+      RValue::Break { span, .. } => *span,
+      RValue::Return { span, .. } => *span,
+      RValue::Let { span, .. } => *span,
+      RValue::If { span, .. } => *span,
+      RValue::While { span, .. } => *span,
+      RValue::Loop { span, .. } => *span,
+      RValue::Match { span, .. } => *span,
+      RValue::Error { span, .. } => *span,
+    }
+  }
+
   pub fn ty(&self) -> &Ty {
     match self {
       RValue::Unit { ty, .. } => ty,
@@ -324,6 +403,7 @@ impl RValue {
       RValue::While { ty, .. } => ty,
       RValue::Loop { ty, .. } => ty,
       RValue::Match { ty, .. } => ty,
+      RValue::Error { ty, .. } => ty,
     }
   }
 }
@@ -71,8 +71,10 @@ impl TVarCtx {
   }
 
   /// Unify two type expressions
-  pub fn unify(&mut self, ty1: &Ty, ty2: &Ty) -> MRes<Ty> {
+  pub fn unify(&mut self, span: Span, ty1: &Ty, ty2: &Ty) -> Result<Ty, CannotUnifyError> {
     match (ty1, ty2) {
+      // Errors were already reported, so they unify with anything
+      (Ty::Error, ty) | (ty, Ty::Error) => Ok(ty.clone()),
       (Ty::Bool, Ty::Bool) => Ok(Ty::Bool),
       (Ty::Uint8, Ty::Uint8) => Ok(Ty::Uint8),
       (Ty::Int8, Ty::Int8) => Ok(Ty::Int8),
@@ -90,43 +92,43 @@ impl TVarCtx {
         let targs = targs1
           .iter()
           .zip(targs2.iter())
-          .map(|(ty1, ty2)| self.unify(ty1, ty2))
-          .monadic_collect()?;
+          .map(|(ty1, ty2)| self.unify(span, ty1, ty2))
+          .monadic_collect2()?;
         Ok(Ty::StructRef(*name, (*def_id, targs)))
       }
       (Ty::UnionRef(name, (def_id, targs1)), Ty::UnionRef(_, (def_id2, targs2))) if def_id == def_id2 => {
         let targs = targs1
           .iter()
           .zip(targs2.iter())
-          .map(|(ty1, ty2)| self.unify(ty1, ty2))
-          .monadic_collect()?;
+          .map(|(ty1, ty2)| self.unify(span, ty1, ty2))
+          .monadic_collect2()?;
         Ok(Ty::UnionRef(*name, (*def_id, targs)))
       }
       (Ty::EnumRef(name, (def_id, targs1)), Ty::EnumRef(_, (def_id2, targs2))) if def_id == def_id2 => {
         let targs = targs1
           .iter()
           .zip(targs2.iter())
-          .map(|(ty1, ty2)| self.unify(ty1, ty2))
-          .monadic_collect()?;
+          .map(|(ty1, ty2)| self.unify(span, ty1, ty2))
+          .monadic_collect2()?;
         Ok(Ty::EnumRef(*name, (*def_id, targs)))
       }
       (Ty::Func(par1, va1, ret1), Ty::Func(par2, va2, ret2)) if par1.len() == par2.len() && va1 == va2 => {
         let mut par = Vec::new();
         for ((n1, t1), (n2, t2)) in par1.iter().zip(par2.iter()) {
           if n1 != n2 {
-            Err(Box::new(CannotUnifyError::Types(ty1.clone(), ty2.clone())))?
+            Err(CannotUnifyError::new(span, Mismatch::Types(ty1.clone(), ty2.clone())))?
           }
-          par.push((*n1, self.unify(t1, t2)?));
+          par.push((*n1, self.unify(span, t1, t2)?));
         }
-        Ok(Ty::Func(par, *va1,Box::new(self.unify(ret1, ret2)?)))
+        Ok(Ty::Func(par, *va1,Box::new(self.unify(span, ret1, ret2)?)))
       }
       (Ty::Ptr(is_mut1, base1), Ty::Ptr(is_mut2, base2)) => {
         let is_mut = if *is_mut1 == IsMut::Yes
           && *is_mut2 == IsMut::Yes { IsMut::Yes } else { IsMut::No };
-        Ok(Ty::Ptr(is_mut, Box::new(self.unify(base1, base2)?)))
+        Ok(Ty::Ptr(is_mut, Box::new(self.unify(span, base1, base2)?)))
       }
       (Ty::Arr(siz1, elem1), Ty::Arr(siz2, elem2)) if siz1 == siz2 => {
-        Ok(Ty::Arr(*siz1, Box::new(self.unify(elem1, elem2)?)))
+        Ok(Ty::Arr(*siz1, Box::new(self.unify(span, elem1, elem2)?)))
       }
       (Ty::Unit, Ty::Unit) => {
         Ok(Ty::Unit)
@@ -135,9 +137,9 @@ impl TVarCtx {
         let mut par = Vec::new();
         for ((n1, t1), (n2, t2)) in par1.iter().zip(par2.iter()) {
           if n1 != n2 {
-            Err(Box::new(CannotUnifyError::Types(ty1.clone(), ty2.clone())))?
+            Err(CannotUnifyError::new(span, Mismatch::Types(ty1.clone(), ty2.clone())))?
           }
-          par.push((*n1, self.unify(t1, t2)?));
+          par.push((*n1, self.unify(span, t1, t2)?));
         }
         Ok(Ty::Tuple(par))
       }
@@ -149,7 +151,7 @@ impl TVarCtx {
         // Apply union-find if they are different
         if root1 != root2 {
           // Unify bounds
-          let unified = self.unify_bounds(&bound1, &bound2)?;
+          let unified = self.unify_bounds(span, &bound1, &bound2)?;
           // Store unified bound in root1
           self.tvars[root1] = unified;
           // Point root2 to root1
@@ -162,24 +164,24 @@ impl TVarCtx {
         // Find root node
         let (root, prev) = self.root(*idx);
         // Unify bounds
-        let unified = self.unify_bounds(&prev, &Bound::Is(ty.clone()))?;
+        let unified = self.unify_bounds(span, &prev, &Bound::Is(ty.clone()))?;
         // Store unified bound
         self.tvars[root] = unified;
         // Return reference to root
         Ok(Ty::Var(root))
       }
-      _ => Err(Box::new(CannotUnifyError::Types(ty1.clone(), ty2.clone())))?
+      _ => Err(CannotUnifyError::new(span, Mismatch::Types(ty1.clone(), ty2.clone())))?
     }
   }
 
   /// Bound a type expression
-  pub fn bound(&mut self, ty: &Ty, bound: &Bound) -> MRes<Ty> {
+  pub fn bound(&mut self, span: Span, ty: &Ty, bound: &Bound) -> Result<Ty, CannotUnifyError> {
     match ty {
       Ty::Var(idx) => {
         // Find root node
         let (root, prev) = self.root(*idx);
         // Unify bounds
-        let unified = self.unify_bounds(&prev, bound)?;
+        let unified = self.unify_bounds(span, &prev, bound)?;
         // Store unified bound
         self.tvars[root] = unified;
         // Return reference to root
@@ -187,17 +189,17 @@ impl TVarCtx {
       }
       _ => {
         // Check type against bound
-        self.unify_bounds(&Bound::Is(ty.clone()), bound)?;
+        self.unify_bounds(span, &Bound::Is(ty.clone()), bound)?;
         // Return clone of type
         Ok(ty.clone())
       }
     }
   }
 
-  fn unify_bounds(&mut self, b1: &Bound, b2: &Bound) -> MRes<Bound> {
+  fn unify_bounds(&mut self, span: Span, b1: &Bound, b2: &Bound) -> Result<Bound, CannotUnifyError> {
     match (b1, b2) {
       // Compare two literal types
-      (Bound::Is(ty1), Bound::Is(ty2)) => Ok(Bound::Is(self.unify(ty1, ty2)?)),
+      (Bound::Is(ty1), Bound::Is(ty2)) => Ok(Bound::Is(self.unify(span, ty1, ty2)?)),
 
       // Check literal type for bound
       (Bound::Is(ty), bound) |
@@ -205,6 +207,8 @@ impl TVarCtx {
         match (bound, ty) {
           (_, Ty::Var(..)) => unreachable!(),
 
+          (_, Ty::Error) => Ok(Bound::Is(Ty::Error)),
+
           (Bound::Any, _) |
           (Bound::Eq,  Ty::Bool|
           Ty::Int8|Ty::Int16|Ty::Int32|Ty::Int64|Ty::Intn|
@@ -220,7 +224,7 @@ impl TVarCtx {
 
           => Ok(Bound::Is(ty.clone())),
 
-          _ => Err(Box::new(CannotUnifyError::TypeDoesNotHaveBound(ty.clone(), bound.clone())))
+          _ => Err(CannotUnifyError::new(span, Mismatch::TypeDoesNotHaveBound(ty.clone(), bound.clone())))
         }
       }
 
@@ -242,7 +246,7 @@ impl TVarCtx {
 
       => Ok(bound.clone()),
 
-      _ => Err(Box::new(CannotUnifyError::Bounds(b1.clone(), b1.clone())))
+      _ => Err(CannotUnifyError::new(span, Mismatch::Bounds(b1.clone(), b2.clone())))
     }
   }
 
@@ -313,12 +317,15 @@ impl TVarCtx {
                             _ => Ty::Var(root)
         }
       }
+      Ty::Error => {
+        Ty::Error
+      }
     }
   }
 
 
   /// Find the canonical form of a list of type expressions
-  pub fn canonical_type_args(&mut self, type_args: &Vec<Ty>) -> Vec<Ty> {
+  pub fn canonical_type_args(&mut self, type_args: &[Ty]) -> Vec<Ty> {
     type_args.iter().map(|ty| self.canonical_ty(ty)).collect()
   }
 
@@ -393,11 +400,14 @@ impl TVarCtx {
           Bound::Eq => Ty::Int32,
         }
       }
+      Ty::Error => {
+        Ty::Error
+      }
     }
   }
 
   /// Find the final bounds of a list of type expressions
-  pub fn final_type_args(&mut self, type_args: &Vec<Ty>) -> Vec<Ty> {
+  pub fn final_type_args(&mut self, type_args: &[Ty]) -> Vec<Ty> {
     type_args.iter().map(|ty| self.final_ty(ty)).collect()
   }
 }
@@ -411,7 +421,17 @@ impl fmt::Debug for TVarCtx {
 }
 
 #[derive(Debug)]
-enum CannotUnifyError {
+pub(crate) struct CannotUnifyError {
+  // Expression whose type was being checked
+  pub span: Span,
+  // Annotation the expected type came from
+  pub annotation: Option<Span>,
+  // Boxed to keep the results of unification small
+  pub mismatch: Box<Mismatch>
+}
+
+#[derive(Debug)]
+pub(crate) enum Mismatch {
   // Cannot unify two bounds
   Bounds(Bound, Bound),
   // Cannot unify two types
@@ -420,20 +440,40 @@ enum CannotUnifyError {
   TypeDoesNotHaveBound(Ty, Bound)
 }
 
+impl CannotUnifyError {
+  fn new(span: Span, mismatch: Mismatch) -> Self {
+    CannotUnifyError { span, annotation: None, mismatch: Box::new(mismatch) }
+  }
+
+  /// Record the annotation that the expected type came from
+  pub fn annotated(mut self, annotation: Span) -> Self {
+    self.annotation = Some(annotation);
+    self
+  }
+
+  pub fn code(&self) -> &'static str {
+    match &*self.mismatch {
+      Mismatch::Types(..) => "E0201",
+      Mismatch::Bounds(..) => "E0202",
+      Mismatch::TypeDoesNotHaveBound(..) => "E0205"
+    }
+  }
+}
+
 impl fmt::Display for CannotUnifyError {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    match self {
-      CannotUnifyError::Bounds(b1, b2) => {
+    match &*self.mismatch {
+      Mismatch::Bounds(b1, b2) => {
         write!(f, "Incompatible type bounds {:?} and {:?}", b1, b2)
       }
-      CannotUnifyError::Types(ty1, ty2) => {
+      Mismatch::Types(ty1, ty2) => {
         write!(f, "Cannot unify types {:?} and {:?}", ty1, ty2)
       }
-      CannotUnifyError::TypeDoesNotHaveBound(ty, bound) => {
+      Mismatch::TypeDoesNotHaveBound(ty, bound) => {
         write!(f, "Cannot bound type {:?} by {:?}", ty, bound)
       }
     }
   }
 }
 
-impl error::Error for CannotUnifyError {}
\ No newline at end of file
+impl error::Error for CannotUnifyError {}
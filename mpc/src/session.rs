@@ -0,0 +1,127 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+use crate::*;
+use crate::source::SourceProvider;
+use std::error;
+
+/// Kinds of artifacts code generation can produce
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ArtifactKind {
+  LLVMIr,
+  LLVMBitcode,
+  Assembly,
+  Object
+}
+
+/// Output of code generation
+
+#[derive(Clone, Debug)]
+pub struct Artifact {
+  pub kind: ArtifactKind,
+  pub data: Vec<u8>
+}
+
+/// Bundle that was parsed, but not yet resolved
+pub struct Parsed(parse::Repository);
+
+/// Bundle with all names resolved
+pub struct Resolved(parse::Repository);
+
+/// Type checked bundle, ready for code generation
+pub struct Analyzed(sema::Collection);
+
+/// A single compilation, with each phase run separately
+///
+/// Nothing is written to the file system, apart from dumps if requested.
+/// Sources are only read through the provider.
+/// On failure, a phase returns every diagnostic reported so far, warnings included.
+
+pub struct Session {
+  options: CompileOptions,
+  sources: SourceMap,
+  warnings: Vec<Warning>,
+  dumper: Dumper
+}
+
+impl Session {
+  pub fn new(provider: Box<dyn SourceProvider>, options: CompileOptions) -> Session {
+    let dumper = Dumper::new(&options.dump);
+    Session { options, sources: SourceMap::new(provider), warnings: Vec::new(), dumper }
+  }
+
+  /// Parse a bundle, starting from its root module
+  pub fn parse(&mut self, input_path: &Path) -> Result<Parsed, Vec<Diagnostic>> {
+    // Modules not found next to the importing one are searched for in the
+    // include directories, then in the standard library
+    let mut search_dirs = self.options.include_dirs.clone();
+    search_dirs.push(self.options.std_dir.clone().unwrap_or_else(|| PathBuf::from(env!("MPC_STD_DIR"))));
+
+    let repo = parse::parse_bundle(&mut self.sources, input_path, search_dirs)
+      .map_err(|error| self.fail(error))?;
+    self.dumper.dump(DumpKind::Ast, |w| {
+      let mut defs: Vec<_> = repo.parsed_defs.iter().collect();
+      defs.sort_by_key(|(id, _)| **id);
+      defs.iter().try_for_each(|(id, def)| writeln!(w, "{:?} {:#?}", id, def))
+    }).map_err(|error| self.fail(error))?;
+    Ok(Parsed(repo))
+  }
+
+  /// Resolve the names used by a bundle
+  pub fn resolve(&mut self, Parsed(mut repo): Parsed) -> Result<Resolved, Vec<Diagnostic>> {
+    resolve::resolve_defs(&mut repo, &mut self.warnings)
+      .map_err(|error| self.fail(error))?;
+    self.dumper.dump(DumpKind::Resolved, |w| {
+      let mut defs: Vec<_> = repo.resolved_defs.iter().collect();
+      defs.sort_by_key(|(id, _)| **id);
+      defs.iter().try_for_each(|(id, def)| writeln!(w, "{:?} {:#?}", id, def))
+    }).map_err(|error| self.fail(error))?;
+    Ok(Resolved(repo))
+  }
+
+  /// Type check a bundle
+  pub fn analyze(&mut self, Resolved(repo): Resolved) -> Result<Analyzed, Vec<Diagnostic>> {
+    let inst_collection = sema::analyze(&repo)
+      .map_err(|error| self.fail(error))?;
+    self.dumper.dump(DumpKind::Typed, |w| {
+      let mut insts: Vec<_> = inst_collection.insts.iter().collect();
+      insts.sort_by_key(|((id, type_args), _)| (*id, format!("{:?}", type_args)));
+      insts.iter().try_for_each(|(id, inst)| writeln!(w, "{:?} {:#?}", id, inst))
+    }).map_err(|error| self.fail(error))?;
+    self.dumper.dump(DumpKind::TVars, |w| writeln!(w, "{:#?}", inst_collection.tctx))
+      .map_err(|error| self.fail(error))?;
+
+    // Denied lints are reported as errors, just stop before code generation
+    if self.warnings.iter().any(|warning| self.options.lints.level(warning.lint) == LintLevel::Deny) {
+      return Err(self.warnings())
+    }
+
+    Ok(Analyzed(inst_collection))
+  }
+
+  /// Generate code, returning one artifact for each kind requested, in order
+  pub fn lower(&mut self, bundle: &mut Analyzed, kinds: &[ArtifactKind]) -> Result<Vec<Artifact>, Vec<Diagnostic>> {
+    lower::compile(&mut bundle.0, &self.sources, kinds, &self.options, &mut self.dumper)
+      .map_err(|error| self.fail(error))
+  }
+
+  /// Paths of the source files read so far
+  pub fn source_paths(&self) -> Vec<&Path> {
+    self.sources.paths().collect()
+  }
+
+  /// Warnings reported so far
+  pub fn warnings(&self) -> Vec<Diagnostic> {
+    diag::from_warnings(&self.sources, &self.warnings, &self.options.lints)
+  }
+
+  /// All diagnostics after a failure
+  pub(crate) fn fail(&self, error: Box<dyn error::Error + Send + Sync>) -> Vec<Diagnostic> {
+    let mut diagnostics = self.warnings();
+    diagnostics.extend(diag::from_error(&self.sources, error));
+    diagnostics
+  }
+}
@@ -0,0 +1,224 @@
+/*
+ * Copyright (c) 2022-2023, Mate Kukri
+ * SPDX-License-Identifier: GPL-2.0-only
+ */
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Identifier of a loaded source file
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct FileId(usize);
+
+/// Byte range inside a source file
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+  pub file: FileId,
+  pub lo: usize,
+  pub hi: usize
+}
+
+impl Span {
+  pub fn new(file: FileId, lo: usize, hi: usize) -> Span {
+    Span { file, lo, hi }
+  }
+
+  /// Smallest span covering both self and other, or just self if they are in different files
+  pub fn to(&self, other: Span) -> Span {
+    if self.file != other.file {
+      return *self
+    }
+    Span { file: self.file, lo: self.lo.min(other.lo), hi: self.hi.max(other.hi) }
+  }
+}
+
+impl fmt::Debug for Span {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}:{}..{}", self.file.0, self.lo, self.hi)
+  }
+}
+
+/// Line and column of a byte offset, both starting at 1
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Position {
+  pub line: usize,
+  pub column: usize
+}
+
+/// Source file contents
+
+pub struct SourceFile {
+  pub path: PathBuf,
+  pub text: String,
+  line_starts: Vec<usize>
+}
+
+impl SourceFile {
+  fn new(path: PathBuf, text: String) -> SourceFile {
+    let line_starts = std::iter::once(0)
+      .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+      .collect();
+    SourceFile { path, text, line_starts }
+  }
+
+  /// Find the position of a byte offset
+  pub fn position(&self, offset: usize) -> Position {
+    let line = match self.line_starts.binary_search(&offset) {
+      Ok(line) => line,
+      Err(next_line) => next_line - 1
+    };
+    let line_start = self.line_starts[line];
+    let column = self.text
+      .get(line_start..offset)
+      .map(|prefix| prefix.chars().count())
+      .unwrap_or(offset - line_start);
+    Position { line: line + 1, column: column + 1 }
+  }
+
+  /// Text of a line, without the line terminator
+  pub fn line(&self, line: usize) -> &str {
+    let start = self.line_starts[line - 1];
+    let end = self.line_starts.get(line).cloned().unwrap_or(self.text.len());
+    self.text[start..end].trim_end_matches(&['\n', '\r'][..])
+  }
+}
+
+/// Where source files are read from
+
+pub trait SourceProvider {
+  /// Check if a file exists
+  fn is_file(&self, path: &Path) -> bool;
+
+  /// Path uniquely identifying a file, no matter how it was reached
+  fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+  /// Read the contents of a file
+  fn read(&self, path: &Path) -> io::Result<String>;
+}
+
+/// Sources read from the file system
+
+pub struct FileSystem;
+
+impl SourceProvider for FileSystem {
+  fn is_file(&self, path: &Path) -> bool {
+    path.is_file()
+  }
+
+  fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+    fs::canonicalize(path)
+  }
+
+  fn read(&self, path: &Path) -> io::Result<String> {
+    fs::read_to_string(path)
+  }
+}
+
+/// Sources held in memory, optionally falling back to another provider
+
+#[derive(Default)]
+pub struct MemorySources {
+  files: HashMap<PathBuf, String>,
+  fallback: Option<Box<dyn SourceProvider>>
+}
+
+impl MemorySources {
+  pub fn new() -> MemorySources {
+    MemorySources { files: HashMap::new(), fallback: None }
+  }
+
+  /// Files not added are looked up in fallback
+  pub fn with_fallback(fallback: Box<dyn SourceProvider>) -> MemorySources {
+    MemorySources { files: HashMap::new(), fallback: Some(fallback) }
+  }
+
+  pub fn add(&mut self, path: impl AsRef<Path>, text: impl Into<String>) {
+    self.files.insert(normalize(path.as_ref()), text.into());
+  }
+}
+
+impl SourceProvider for MemorySources {
+  fn is_file(&self, path: &Path) -> bool {
+    if self.files.contains_key(&normalize(path)) {
+      return true
+    }
+    self.fallback.as_ref().is_some_and(|fallback| fallback.is_file(path))
+  }
+
+  fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+    let normalized = normalize(path);
+    if self.files.contains_key(&normalized) {
+      return Ok(normalized)
+    }
+    match &self.fallback {
+      Some(fallback) => fallback.canonicalize(path),
+      None => Err(io::Error::from(io::ErrorKind::NotFound))
+    }
+  }
+
+  fn read(&self, path: &Path) -> io::Result<String> {
+    if let Some(text) = self.files.get(&normalize(path)) {
+      return Ok(text.clone())
+    }
+    match &self.fallback {
+      Some(fallback) => fallback.read(path),
+      None => Err(io::Error::from(io::ErrorKind::NotFound))
+    }
+  }
+}
+
+/// Resolve `.` and `..` components without consulting the file system
+fn normalize(path: &Path) -> PathBuf {
+  let mut normalized = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => (),
+      Component::ParentDir => match normalized.components().next_back() {
+        Some(Component::Normal(_)) => { normalized.pop(); }
+        Some(Component::RootDir) => (),
+        _ => normalized.push("..")
+      }
+      component => normalized.push(component)
+    }
+  }
+  normalized
+}
+
+/// All source files read during a compilation
+
+pub struct SourceMap {
+  provider: Box<dyn SourceProvider>,
+  files: Vec<SourceFile>
+}
+
+impl SourceMap {
+  pub fn new(provider: Box<dyn SourceProvider>) -> SourceMap {
+    SourceMap { provider, files: Vec::new() }
+  }
+
+  /// Where the files are read from
+  pub fn provider(&self) -> &dyn SourceProvider {
+    &*self.provider
+  }
+
+  pub fn add(&mut self, path: &Path, text: String) -> FileId {
+    let id = FileId(self.files.len());
+    self.files.push(SourceFile::new(path.to_path_buf(), text));
+    id
+  }
+
+  pub fn file(&self, id: FileId) -> &SourceFile {
+    &self.files[id.0]
+  }
+
+  /// Paths of all files, in the order they were read
+  pub fn paths(&self) -> impl Iterator<Item = &Path> {
+    self.files.iter().map(|file| file.path.as_path())
+  }
+}
@@ -5,6 +5,7 @@
 
 use std::collections::HashSet;
 use std::error::Error;
+use std::ffi::CStr;
 use std::fmt;
 use std::sync::Mutex;
 
@@ -12,6 +13,36 @@ use std::sync::Mutex;
 
 pub type MRes<T> = Result<T, Box<dyn Error + Send + Sync>>;
 
+/// Errors collected by a pass that carries on after a failure
+
+#[derive(Debug)]
+pub struct ErrorList(pub Vec<Box<dyn Error + Send + Sync>>);
+
+impl ErrorList {
+  /// Fail with the collected errors, if there were any
+  pub fn check(errors: Vec<Box<dyn Error + Send + Sync>>) -> MRes<()> {
+    if errors.is_empty() {
+      Ok(())
+    } else {
+      Err(Box::new(ErrorList(errors)))
+    }
+  }
+}
+
+impl fmt::Display for ErrorList {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (index, error) in self.0.iter().enumerate() {
+      if index > 0 {
+        writeln!(f)?;
+      }
+      write!(f, "{}", error)?;
+    }
+    Ok(())
+  }
+}
+
+impl Error for ErrorList {}
+
 /// Globally de-duped strings
 
 static STRING_TABLE: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
@@ -20,7 +51,7 @@ fn string_table_intern(s: &str) -> &'static str {
   let s = to_owned_c(s);
   let mut locked = STRING_TABLE.lock().unwrap();
   locked
-    .get_or_insert_with(|| HashSet::new())
+    .get_or_insert_with(HashSet::new)
     .get_or_insert_with(&*s, |s| Box::leak(str::to_owned(s).into_boxed_str()))
 }
 
@@ -46,8 +77,8 @@ impl RefStr {
     &self.s[0..self.s.len() - 1]
   }
 
-  pub fn borrow_c(&self) -> *const i8 {
-    self.s.as_ptr() as _
+  pub fn borrow_c(&self) -> &'static CStr {
+    CStr::from_bytes_with_nul(self.s.as_bytes()).unwrap()
   }
 }
 
@@ -65,7 +96,7 @@ impl fmt::Debug for RefStr {
 
 impl PartialEq for RefStr {
   fn eq(&self, rhs: &RefStr) -> bool {
-    self.s as *const str == rhs.s as *const str
+    std::ptr::eq(self.s, rhs.s)
   }
 }
 
@@ -79,7 +110,7 @@ impl std::hash::Hash for RefStr {
 
 // Linear search a vector of pairs
 
-pub fn lin_search<'vec, T: PartialEq, U>(vec: &'vec Vec<(T, U)>, want: &T) -> Option<(usize, &'vec U)> {
+pub fn lin_search<'vec, T: PartialEq, U>(vec: &'vec [(T, U)], want: &T) -> Option<(usize, &'vec U)> {
   for (idx, (key, val)) in vec.iter().enumerate() {
     if key == want {
       return Some((idx, val));
@@ -143,6 +174,41 @@ impl<I, O, E> MonadicCollect2<O, E> for I
 
 /// Concatenate two vectors
 pub fn concat<T>(mut a: Vec<T>, b: Vec<T>) -> Vec<T> {
-  a.extend(b.into_iter());
+  a.extend(b);
   a
 }
+
+/// Edit distance between two strings, counting insertions, deletions,
+/// substitutions and transpositions of adjacent characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+  let a: Vec<char> = a.chars().collect();
+  let b: Vec<char> = b.chars().collect();
+  let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+  for (i, row) in d.iter_mut().enumerate() { row[0] = i; }
+  for (j, cell) in d[0].iter_mut().enumerate() { *cell = j; }
+  for i in 1..=a.len() {
+    for j in 1..=b.len() {
+      let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+      d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+      if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+        d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+      }
+    }
+  }
+  d[a.len()][b.len()]
+}
+
+/// Find the candidate closest to a misspelled name, if any is close enough
+pub fn closest_match<I>(name: RefStr, candidates: I) -> Option<RefStr>
+  where I: IntoIterator<Item=RefStr>
+{
+  let max_distance = name.borrow_rs().chars().count().max(3) / 3;
+  candidates
+    .into_iter()
+    .filter(|candidate| *candidate != name)
+    .map(|candidate| (edit_distance(name.borrow_rs(), candidate.borrow_rs()), candidate))
+    .filter(|(distance, _)| *distance <= max_distance)
+    // Break ties by name, so the result does not depend on hash map order
+    .min_by(|(d1, c1), (d2, c2)| (d1, c1.borrow_rs()).cmp(&(d2, c2.borrow_rs())))
+    .map(|(_, candidate)| candidate)
+}
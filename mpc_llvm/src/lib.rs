@@ -12,17 +12,183 @@
 
 
 use llvm_sys::*;
+use llvm_sys::bit_writer::*;
+use llvm_sys::debuginfo::*;
 use llvm_sys::core::*;
 use llvm_sys::prelude::*;
 use llvm_sys::target::*;
 use llvm_sys::target_machine::*;
+use llvm_sys::transforms::pass_builder::*;
+use llvm_sys::error::*;
 pub use llvm_sys::{LLVMIntPredicate::*,
                    LLVMRealPredicate::*};
 
-use std::ffi::{c_char, CString};
-use std::io;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::io::Read;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
 use std::marker::PhantomData;
-use std::path::Path;
+
+/// Optimization level
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptLevel {
+  /// No IR optimizations, with the default code generation level
+  Default,
+  O0,
+  O1,
+  O2,
+  O3,
+  Os
+}
+
+impl OptLevel {
+  fn codegen_level(self) -> LLVMCodeGenOptLevel {
+    match self {
+      OptLevel::Default => LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+      OptLevel::O0 => LLVMCodeGenOptLevel::LLVMCodeGenLevelNone,
+      OptLevel::O1 => LLVMCodeGenOptLevel::LLVMCodeGenLevelLess,
+      OptLevel::O2 => LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+      OptLevel::O3 => LLVMCodeGenOptLevel::LLVMCodeGenLevelAggressive,
+      OptLevel::Os => LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+    }
+  }
+
+  /// New pass manager pipeline matching this level
+  pub fn pipeline(self) -> Option<&'static str> {
+    match self {
+      OptLevel::Default => None,
+      OptLevel::O0 => Some("default<O0>"),
+      OptLevel::O1 => Some("default<O1>"),
+      OptLevel::O2 => Some("default<O2>"),
+      OptLevel::O3 => Some("default<O3>"),
+      OptLevel::Os => Some("default<Os>"),
+    }
+  }
+
+  /// Whether the IR is optimized at this level
+  pub fn is_optimized(self) -> bool {
+    !matches!(self, OptLevel::Default | OptLevel::O0)
+  }
+}
+
+/// Relocation model
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RelocModel {
+  Default,
+  Static,
+  Pic,
+  DynamicNoPic
+}
+
+impl RelocModel {
+  fn l_reloc_mode(self) -> LLVMRelocMode {
+    match self {
+      RelocModel::Default => LLVMRelocMode::LLVMRelocDefault,
+      RelocModel::Static => LLVMRelocMode::LLVMRelocStatic,
+      RelocModel::Pic => LLVMRelocMode::LLVMRelocPIC,
+      RelocModel::DynamicNoPic => LLVMRelocMode::LLVMRelocDynamicNoPic,
+    }
+  }
+}
+
+/// Code model
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodeModel {
+  Default,
+  Tiny,
+  Small,
+  Kernel,
+  Medium,
+  Large
+}
+
+impl CodeModel {
+  fn l_code_model(self) -> LLVMCodeModel {
+    match self {
+      CodeModel::Default => LLVMCodeModel::LLVMCodeModelDefault,
+      CodeModel::Tiny => LLVMCodeModel::LLVMCodeModelTiny,
+      CodeModel::Small => LLVMCodeModel::LLVMCodeModelSmall,
+      CodeModel::Kernel => LLVMCodeModel::LLVMCodeModelKernel,
+      CodeModel::Medium => LLVMCodeModel::LLVMCodeModelMedium,
+      CodeModel::Large => LLVMCodeModel::LLVMCodeModelLarge,
+    }
+  }
+}
+
+/// Linkage of a global value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Linkage {
+  External,
+  Internal,
+  Private
+}
+
+impl Linkage {
+  fn l_linkage(self) -> LLVMLinkage {
+    match self {
+      Linkage::External => LLVMLinkage::LLVMExternalLinkage,
+      Linkage::Internal => LLVMLinkage::LLVMInternalLinkage,
+      Linkage::Private => LLVMLinkage::LLVMPrivateLinkage,
+    }
+  }
+}
+
+/// Encoding of a basic type in debug info
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DIEncoding {
+  Boolean,
+  Float,
+  Signed,
+  Unsigned
+}
+
+impl DIEncoding {
+  fn l_encoding(self) -> LLVMDWARFTypeEncoding {
+    // DW_ATE_* constants from the DWARF standard
+    match self {
+      DIEncoding::Boolean => 0x02,
+      DIEncoding::Float => 0x04,
+      DIEncoding::Signed => 0x05,
+      DIEncoding::Unsigned => 0x07,
+    }
+  }
+}
+
+/// Where an entity described by debug info is declared
+#[derive(Clone, Copy)]
+pub struct DIDecl<'ctx> {
+  pub scope: Metadata<'ctx>,
+  pub file: Metadata<'ctx>,
+  pub line: usize
+}
+
+/// Target machine configuration
+#[derive(Clone, Debug)]
+pub struct TargetOptions {
+  /// Target triplet, the host is targeted if not specified
+  pub triple: Option<String>,
+  /// CPU to generate code for, "native" selects the host CPU
+  pub cpu: Option<String>,
+  /// Comma separated list of features to enable (+feature) or disable (-feature)
+  pub features: Option<String>,
+  pub reloc_model: RelocModel,
+  pub code_model: CodeModel,
+  /// Keep frame pointers in every function
+  pub frame_pointers: bool
+}
+
+impl Default for TargetOptions {
+  fn default() -> Self {
+    TargetOptions {
+      triple: None,
+      cpu: None,
+      features: None,
+      reloc_model: RelocModel::Pic,
+      code_model: CodeModel::Default,
+      frame_pointers: false
+    }
+  }
+}
 
 pub struct Target {
   l_machine: LLVMTargetMachineRef,
@@ -68,6 +234,19 @@ pub struct Value<'ctx> {
   lifetime: PhantomData<&'ctx Context>,
 }
 
+pub struct DIBuilder<'ctx> {
+  l_builder: LLVMDIBuilderRef,
+  l_context: LLVMContextRef,
+  lifetime: PhantomData<&'ctx Context>,
+}
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Metadata<'ctx> {
+  l_metadata: LLVMMetadataRef,
+  lifetime: PhantomData<&'ctx Context>,
+}
+
 impl Drop for Target {
   fn drop(&mut self) {
     unsafe {
@@ -104,8 +283,16 @@ impl<'ctx> Drop for Builder<'ctx> {
   }
 }
 
+impl<'ctx> Drop for DIBuilder<'ctx> {
+  fn drop(&mut self) {
+    unsafe {
+      LLVMDisposeDIBuilder(self.l_builder)
+    }
+  }
+}
+
 impl Target {
-  pub fn native() -> Target {
+  pub fn new(options: &TargetOptions, opt_level: OptLevel) -> Result<Target, String> {
     unsafe {
       LLVM_InitializeAllTargetInfos();
       LLVM_InitializeAllTargets();
@@ -113,69 +300,66 @@ impl Target {
       LLVM_InitializeAllAsmParsers();
       LLVM_InitializeAllAsmPrinters();
 
-      let l_triple = LLVMGetDefaultTargetTriple();
-      let l_cpu_name = LLVMGetHostCPUName();
-      let l_cpu_features = LLVMGetHostCPUFeatures();
+      let c_triple = options.triple.as_deref().map(c_string).transpose()?;
+      let c_cpu = options.cpu.as_deref().map(c_string).transpose()?;
+      let c_features = options.features.as_deref().map(c_string).transpose()?;
+      if let Some(feature) = options.features.iter()
+          .flat_map(|features| features.split(','))
+          .find(|feature| !feature.is_empty() && !feature.starts_with(['+', '-'])) {
+        return Err(format!("Target feature {} must start with + or -", feature))
+      }
+
+      // The host CPU is only a sensible default when compiling for the host
+      let (l_triple, native) = match &c_triple {
+        Some(c_triple) => (LLVMCreateMessage(c_triple.as_ptr()), false),
+        None => (LLVMGetDefaultTargetTriple(), true)
+      };
 
       let mut l_target = std::ptr::null_mut();
       let mut l_errors = std::ptr::null_mut();
-      LLVMGetTargetFromTriple(l_triple, &mut l_target, &mut l_errors);
-      assert!(l_errors.is_null());
+      if LLVMGetTargetFromTriple(l_triple, &mut l_target, &mut l_errors) != 0 {
+        let message = std::ffi::CStr::from_ptr(l_errors).to_string_lossy().into_owned();
+        LLVMDisposeMessage(l_errors);
+        LLVMDisposeMessage(l_triple);
+        return Err(message)
+      }
+
+      let host_cpu = options.cpu.as_deref() == Some("native") || (native && options.cpu.is_none());
+      let l_cpu_name = match &c_cpu {
+        _ if host_cpu => LLVMGetHostCPUName(),
+        Some(c_cpu) => LLVMCreateMessage(c_cpu.as_ptr()),
+        None => LLVMCreateMessage(empty_cstr())
+      };
+      let l_cpu_features = match &c_features {
+        Some(c_features) => LLVMCreateMessage(c_features.as_ptr()),
+        None if host_cpu => LLVMGetHostCPUFeatures(),
+        None => LLVMCreateMessage(empty_cstr())
+      };
 
-      let l_machine = LLVMCreateTargetMachine(
+      // NOTE: LLVM only warns about unknown CPUs and features on stderr,
+      // then carries on with a subtarget that might not even work
+      let (l_machine, warnings) = capture_stderr(|| LLVMCreateTargetMachine(
         l_target,
         l_triple,
         l_cpu_name,
         l_cpu_features,
-        LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
-        LLVMRelocMode::LLVMRelocPIC,
-        LLVMCodeModel::LLVMCodeModelDefault);
+        opt_level.codegen_level(),
+        options.reloc_model.l_reloc_mode(),
+        options.code_model.l_code_model()));
 
       LLVMDisposeMessage(l_cpu_name);
       LLVMDisposeMessage(l_cpu_features);
 
-      let l_layout = LLVMCreateTargetDataLayout(l_machine);
-      let l_layout_str = LLVMCopyStringRepOfTargetData(l_layout);
-
-      Target {
-        l_machine,
-        l_layout,
-        l_triple,
-        l_layout_str
+      if let Some(message) = unknown_subtarget(&warnings) {
+        LLVMDisposeTargetMachine(l_machine);
+        LLVMDisposeMessage(l_triple);
+        return Err(message)
       }
-    }
-  }
-
-  pub fn from_triplet(triple: &str) -> Option<Target> {
-    unsafe {
-      LLVM_InitializeAllTargetInfos();
-      LLVM_InitializeAllTargets();
-      LLVM_InitializeAllTargetMCs();
-      LLVM_InitializeAllAsmParsers();
-      LLVM_InitializeAllAsmPrinters();
-
-
-      let c_triple = CString::new(triple).unwrap();
-      let l_triple = LLVMCreateMessage(c_triple.as_ptr());
-
-      let mut l_target = std::ptr::null_mut();
-      let mut l_errors = std::ptr::null_mut();
-      LLVMGetTargetFromTriple(l_triple, &mut l_target, &mut l_errors);
-      if !l_errors.is_null() { return None }
-
-      let l_machine = LLVMCreateTargetMachine(
-        l_target,
-        l_triple,
-        empty_cstr(),
-        empty_cstr(),
-        LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
-        LLVMRelocMode::LLVMRelocPIC,
-        LLVMCodeModel::LLVMCodeModelDefault);
 
       let l_layout = LLVMCreateTargetDataLayout(l_machine);
       let l_layout_str = LLVMCopyStringRepOfTargetData(l_layout);
 
-      Some(Target {
+      Ok(Target {
         l_machine,
         l_layout,
         l_triple,
@@ -196,26 +380,61 @@ impl Target {
     }
   }
 
-  pub fn write_llvm_ir(&self, module: Module<'_>, path: &Path) -> Result<(), io::Error> {
+  /// Offset of a structure field in bytes
+  pub fn offset_of(&mut self, ty: Type<'_>, index: usize) -> usize {
+    unsafe {
+      LLVMOffsetOfElement(self.l_layout, ty.l_type, index as _) as _
+    }
+  }
+
+  pub fn run_passes(&self, module: &Module<'_>, passes: &str) -> Result<(), String> {
+    unsafe {
+      let c_passes = CString::new(passes).unwrap();
+      let l_options = LLVMCreatePassBuilderOptions();
+
+      let l_error = LLVMRunPasses(module.l_module,
+                                  c_passes.as_ptr(),
+                                  self.l_machine,
+                                  l_options);
+
+      LLVMDisposePassBuilderOptions(l_options);
+
+      // Invalid pass pipelines are reported back to the caller
+      if l_error.is_null() {
+        Ok(())
+      } else {
+        let l_message = LLVMGetErrorMessage(l_error);
+        let message = std::ffi::CStr::from_ptr(l_message).to_string_lossy().into_owned();
+        LLVMDisposeErrorMessage(l_message);
+        Err(message)
+      }
+    }
+  }
+
+  pub fn emit_llvm_ir(&self, module: &Module<'_>) -> Vec<u8> {
     unsafe {
       // Create string representation of module
       let module_str = LLVMPrintModuleToString(module.l_module);
 
-      // Write string to file
-      let data: &[u8] = std::slice::from_raw_parts(
+      // Copy string out of LLVM's allocation
+      let data = std::slice::from_raw_parts(
         module_str as *const u8,
-        c_strlen(module_str));
-      std::fs::write(path, data)?;
+        c_strlen(module_str)).to_vec();
 
       // Free string
       LLVMDisposeMessage(module_str);
 
-      // We are okay
-      Ok(())
+      data
+    }
+  }
+
+  pub fn emit_bitcode(&self, module: &Module<'_>) -> Vec<u8> {
+    unsafe {
+      take_buffer(LLVMWriteBitcodeToMemoryBuffer(module.l_module))
     }
   }
 
-  pub fn write_machine_code(&self, module: Module<'_>, textual: bool, path: &Path) -> Result<(), io::Error> {
+  pub fn emit_machine_code(&self, module: &Module<'_>, textual: bool) -> Vec<u8> {
     unsafe {
       let file_type = if textual {
         LLVMCodeGenFileType::LLVMAssemblyFile
@@ -223,13 +442,17 @@ impl Target {
         LLVMCodeGenFileType::LLVMObjectFile
       };
 
+      // Code generation modifies the IR, so work on a copy of the module
+      // to allow emitting more than one artifact from it
+      let l_module = LLVMCloneModule(module.l_module);
+
       let mut errors = std::ptr::null_mut();
       let mut buffer = std::ptr::null_mut();
 
       // Ask LLVM put the data into a buffer for us
       LLVMTargetMachineEmitToMemoryBuffer(
         self.l_machine,
-        module.l_module,
+        l_module,
         file_type,
         &mut errors,
         &mut buffer);
@@ -237,21 +460,18 @@ impl Target {
       // NOTE: Generating un-compilable IR is considered a bug
       assert!(errors.is_null());
 
-      // Write the data from above to the output file
-      let data: &[u8] = std::slice::from_raw_parts(
-        LLVMGetBufferStart(buffer) as *const u8,
-        LLVMGetBufferSize(buffer));
-      std::fs::write(path, data)?;
-
-      // Free buffer
-      LLVMDisposeMemoryBuffer(buffer);
-
-      // We are all okay
-      Ok(())
+      LLVMDisposeModule(l_module);
+      take_buffer(buffer)
     }
   }
 }
 
+impl Default for Context {
+  fn default() -> Self {
+    Context::new()
+  }
+}
+
 impl<'ctx> Context {
   pub fn new() -> Context {
     unsafe {
@@ -270,10 +490,10 @@ impl<'ctx> Context {
     }
   }
 
-  pub fn module(&'ctx self, name: *const c_char) -> Module<'ctx> {
+  pub fn module(&'ctx self, name: &CStr) -> Module<'ctx> {
     unsafe {
       Module {
-        l_module: LLVMModuleCreateWithNameInContext(name,
+        l_module: LLVMModuleCreateWithNameInContext(name.as_ptr(),
                                                     self.l_context),
         lifetime: PhantomData
       }
@@ -414,6 +634,18 @@ impl<'ctx> Context {
     }
   }
 
+  /// Integer constant, sign extended or truncated to the width of ty
+  pub fn const_signed_int(&'ctx self, ty: Type<'ctx>, val: i64) -> Value<'ctx> {
+    assert!(ty.is_int());
+
+    unsafe {
+      Value {
+        l_value: LLVMConstInt(ty.l_type, val as _, 1),
+        lifetime: PhantomData
+      }
+    }
+  }
+
   pub fn const_flt(&'ctx self, ty: Type<'ctx>, val: f64) -> Value<'ctx> {
     assert!(ty.is_flt());
 
@@ -1107,6 +1339,24 @@ impl<'ctx> Builder<'ctx> {
     }
   }
 
+  pub fn unreachable(&self) {
+    unsafe {
+      LLVMBuildUnreachable(self.l_builder);
+    }
+  }
+
+  pub fn extract_value(&self, agg: Value<'ctx>, index: usize) -> Value<'ctx> {
+    unsafe {
+      Value {
+        l_value: LLVMBuildExtractValue(self.l_builder,
+                                       agg.l_value,
+                                       index as _,
+                                       empty_cstr()),
+        lifetime: PhantomData
+      }
+    }
+  }
+
   pub fn switch(&self, ctrl: Value<'ctx>,
                 cases: &[(Value<'ctx>, Block<'ctx>)],
                 default: Block<'ctx>) {
@@ -1122,6 +1372,27 @@ impl<'ctx> Builder<'ctx> {
   }
 }
 
+impl<'ctx> Builder<'ctx> {
+  /// Source location attached to the instructions built
+  pub fn debug_location(&self) -> Option<Metadata<'ctx>> {
+    unsafe {
+      let l_metadata = LLVMGetCurrentDebugLocation2(self.l_builder);
+      if l_metadata.is_null() {
+        None
+      } else {
+        Some(Metadata { l_metadata, lifetime: PhantomData })
+      }
+    }
+  }
+
+  pub fn set_debug_location(&self, location: Option<Metadata<'ctx>>) {
+    unsafe {
+      LLVMSetCurrentDebugLocation2(self.l_builder,
+                                   location.map_or(std::ptr::null_mut(), |location| location.l_metadata))
+    }
+  }
+}
+
 impl<'ctx> Module<'ctx> {
   pub fn set_target(&self, target: &Target) {
     unsafe {
@@ -1130,21 +1401,69 @@ impl<'ctx> Module<'ctx> {
     }
   }
 
-  pub fn add_global(&self, name: *const c_char, ty: Type<'ctx>) -> Value<'ctx> {
+  pub fn add_global(&self, name: &CStr, ty: Type<'ctx>) -> Value<'ctx> {
     unsafe {
       Value {
-        l_value: LLVMAddGlobal(self.l_module, ty.l_type, name),
+        l_value: LLVMAddGlobal(self.l_module, ty.l_type, name.as_ptr()),
         lifetime: PhantomData
       }
     }
   }
 
-  pub fn add_function(&self, name: *const c_char, ty: Type<'ctx>) -> Value<'ctx> {
+  pub fn add_function(&self, name: &CStr, ty: Type<'ctx>) -> Value<'ctx> {
     assert!(ty.is_function());
 
     unsafe {
       Value {
-        l_value: LLVMAddFunction(self.l_module, name, ty.l_type),
+        l_value: LLVMAddFunction(self.l_module, name.as_ptr(), ty.l_type),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  pub fn get_function(&self, name: &CStr) -> Option<Value<'ctx>> {
+    unsafe {
+      let l_value = LLVMGetNamedFunction(self.l_module, name.as_ptr());
+      if l_value.is_null() {
+        None
+      } else {
+        Some(Value { l_value, lifetime: PhantomData })
+      }
+    }
+  }
+
+  /// Declaration of an intrinsic, along with its function type
+  pub fn intrinsic(&self, name: &str, overloads: &[Type<'ctx>]) -> (Type<'ctx>, Value<'ctx>) {
+    unsafe {
+      let id = LLVMLookupIntrinsicID(name.as_ptr() as _, name.len());
+      assert_ne!(id, 0);
+      let l_context = LLVMGetModuleContext(self.l_module);
+      let ty = Type {
+        l_type: LLVMIntrinsicGetType(l_context, id, overloads.as_ptr() as _, overloads.len()),
+        lifetime: PhantomData
+      };
+      let value = Value {
+        l_value: LLVMGetIntrinsicDeclaration(self.l_module, id, overloads.as_ptr() as _, overloads.len()),
+        lifetime: PhantomData
+      };
+      (ty, value)
+    }
+  }
+
+  /// Create a builder for the debug info of this module
+  pub fn di_builder(&self) -> DIBuilder<'ctx> {
+    unsafe {
+      // Debug info is silently dropped without a version flag
+      let l_context = LLVMGetModuleContext(self.l_module);
+      for (key, version) in [("Debug Info Version", LLVMDebugMetadataVersion()), ("Dwarf Version", 4)] {
+        let l_version = LLVMConstInt(LLVMInt32TypeInContext(l_context), version as _, 0);
+        LLVMAddModuleFlag(self.l_module, LLVMModuleFlagBehavior::LLVMModuleFlagBehaviorWarning,
+                          key.as_ptr() as _, key.len(),
+                          LLVMValueAsMetadata(l_version));
+      }
+      DIBuilder {
+        l_builder: LLVMCreateDIBuilder(self.l_module),
+        l_context,
         lifetime: PhantomData
       }
     }
@@ -1155,6 +1474,15 @@ impl<'ctx> Module<'ctx> {
       LLVMDumpModule(self.l_module);
     }
   }
+
+  pub fn print_to_string(&self) -> String {
+    unsafe {
+      let module_str = LLVMPrintModuleToString(self.l_module);
+      let result = std::ffi::CStr::from_ptr(module_str).to_string_lossy().into_owned();
+      LLVMDisposeMessage(module_str);
+      result
+    }
+  }
 }
 
 impl<'ctx> Type<'ctx> {
@@ -1203,6 +1531,50 @@ impl<'ctx> Value<'ctx> {
     }
   }
 
+  pub fn add_string_attribute(&self, key: &str, value: &str) {
+    unsafe {
+      assert!(!LLVMIsAFunction(self.l_value).is_null());
+      let l_context = LLVMGetTypeContext(LLVMTypeOf(self.l_value));
+      let l_attribute = LLVMCreateStringAttribute(l_context,
+                                                  key.as_ptr() as _, key.len() as _,
+                                                  value.as_ptr() as _, value.len() as _);
+      LLVMAddAttributeAtIndex(self.l_value, LLVMAttributeFunctionIndex, l_attribute);
+    }
+  }
+
+  pub fn add_attribute(&self, name: &str) {
+    unsafe {
+      assert!(!LLVMIsAFunction(self.l_value).is_null());
+      let l_context = LLVMGetTypeContext(LLVMTypeOf(self.l_value));
+      let kind = LLVMGetEnumAttributeKindForName(name.as_ptr() as _, name.len());
+      assert_ne!(kind, 0);
+      let l_attribute = LLVMCreateEnumAttribute(l_context, kind, 0);
+      LLVMAddAttributeAtIndex(self.l_value, LLVMAttributeFunctionIndex, l_attribute);
+    }
+  }
+
+  pub fn set_linkage(&self, linkage: Linkage) {
+    unsafe {
+      assert!(!LLVMIsAGlobalValue(self.l_value).is_null());
+      LLVMSetLinkage(self.l_value, linkage.l_linkage());
+    }
+  }
+
+  /// Mark a global variable as never written, allowing it to be placed in a read-only section
+  pub fn set_constant(&self, constant: bool) {
+    unsafe {
+      assert!(!LLVMIsAGlobalVariable(self.l_value).is_null());
+      LLVMSetGlobalConstant(self.l_value, constant as _);
+    }
+  }
+
+  pub fn set_subprogram(&self, subprogram: Metadata<'ctx>) {
+    unsafe {
+      assert!(!LLVMIsAFunction(self.l_value).is_null());
+      LLVMSetSubprogram(self.l_value, subprogram.l_metadata);
+    }
+  }
+
   pub fn get_param(&self, index: usize) -> Value<'ctx> {
     unsafe {
       assert!(!LLVMIsAFunction(self.l_value).is_null());
@@ -1224,9 +1596,407 @@ impl<'ctx> Value<'ctx> {
   }
 }
 
+impl<'ctx> DIBuilder<'ctx> {
+  /// Construct deferred descriptors, must be called once everything was added
+  pub fn finalize(&self) {
+    unsafe {
+      LLVMDIBuilderFinalize(self.l_builder)
+    }
+  }
+
+  pub fn file(&self, name: &str, directory: &str) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateFile(self.l_builder,
+                                            name.as_ptr() as _, name.len(),
+                                            directory.as_ptr() as _, directory.len()),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  pub fn compile_unit(&self, file: Metadata<'ctx>, producer: &str, optimized: bool) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateCompileUnit(self.l_builder,
+                                                   LLVMDWARFSourceLanguage::LLVMDWARFSourceLanguageC,
+                                                   file.l_metadata,
+                                                   producer.as_ptr() as _, producer.len(),
+                                                   optimized as _,
+                                                   empty_cstr(), 0,
+                                                   0,
+                                                   empty_cstr(), 0,
+                                                   LLVMDWARFEmissionKind::LLVMDWARFEmissionKindFull,
+                                                   0, 0, 0,
+                                                   empty_cstr(), 0,
+                                                   empty_cstr(), 0),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  pub fn function(&self,
+                  decl: DIDecl<'ctx>,
+                  name: &str,
+                  linkage_name: &str,
+                  ty: Metadata<'ctx>,
+                  is_local: bool,
+                  optimized: bool) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateFunction(self.l_builder,
+                                                decl.scope.l_metadata,
+                                                name.as_ptr() as _, name.len(),
+                                                linkage_name.as_ptr() as _, linkage_name.len(),
+                                                decl.file.l_metadata,
+                                                decl.line as _,
+                                                ty.l_metadata,
+                                                is_local as _,
+                                                1,
+                                                decl.line as _,
+                                                LLVMDIFlagPrototyped,
+                                                optimized as _),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  /// Source location inside scope, both line and column start at 1
+  pub fn location(&self, line: usize, column: usize, scope: Metadata<'ctx>) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateDebugLocation(self.l_context,
+                                                     line as _,
+                                                     column as _,
+                                                     scope.l_metadata,
+                                                     std::ptr::null_mut()),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  /// Function type, a return type of None meaning void
+  pub fn subroutine_type(&self, file: Metadata<'ctx>, ret: Option<Metadata<'ctx>>, params: &[Metadata<'ctx>]) -> Metadata<'ctx> {
+    let mut l_types: Vec<LLVMMetadataRef> = std::iter::once(ret.map_or(std::ptr::null_mut(), |ret| ret.l_metadata))
+      .chain(params.iter().map(|param| param.l_metadata))
+      .collect();
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateSubroutineType(self.l_builder,
+                                                      file.l_metadata,
+                                                      l_types.as_mut_ptr(),
+                                                      l_types.len() as _,
+                                                      LLVMDIFlagZero),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  pub fn basic_type(&self, name: &str, size_bits: usize, encoding: DIEncoding) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateBasicType(self.l_builder,
+                                                 name.as_ptr() as _, name.len(),
+                                                 size_bits as _,
+                                                 encoding.l_encoding(),
+                                                 LLVMDIFlagZero),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  pub fn pointer_type(&self, pointee: Metadata<'ctx>, size_bits: usize) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreatePointerType(self.l_builder,
+                                                   pointee.l_metadata,
+                                                   size_bits as _,
+                                                   0,
+                                                   0,
+                                                   empty_cstr(), 0),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  pub fn array_type(&self, element: Metadata<'ctx>, count: usize, size_bits: usize, align_bits: usize) -> Metadata<'ctx> {
+    unsafe {
+      let mut l_subscripts = [LLVMDIBuilderGetOrCreateSubrange(self.l_builder, 0, count as _)];
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateArrayType(self.l_builder,
+                                                 size_bits as _,
+                                                 align_bits as _,
+                                                 element.l_metadata,
+                                                 l_subscripts.as_mut_ptr(),
+                                                 l_subscripts.len() as _),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  pub fn struct_type(&self,
+                     decl: DIDecl<'ctx>,
+                     name: &str,
+                     size_bits: usize,
+                     align_bits: usize,
+                     members: &[Metadata<'ctx>]) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateStructType(self.l_builder,
+                                                  decl.scope.l_metadata,
+                                                  name.as_ptr() as _, name.len(),
+                                                  decl.file.l_metadata,
+                                                  decl.line as _,
+                                                  size_bits as _,
+                                                  align_bits as _,
+                                                  LLVMDIFlagZero,
+                                                  std::ptr::null_mut(),
+                                                  members.as_ptr() as _,
+                                                  members.len() as _,
+                                                  0,
+                                                  std::ptr::null_mut(),
+                                                  empty_cstr(), 0),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  pub fn union_type(&self,
+                    decl: DIDecl<'ctx>,
+                    name: &str,
+                    size_bits: usize,
+                    align_bits: usize,
+                    members: &[Metadata<'ctx>]) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateUnionType(self.l_builder,
+                                                 decl.scope.l_metadata,
+                                                 name.as_ptr() as _, name.len(),
+                                                 decl.file.l_metadata,
+                                                 decl.line as _,
+                                                 size_bits as _,
+                                                 align_bits as _,
+                                                 LLVMDIFlagZero,
+                                                 members.as_ptr() as _,
+                                                 members.len() as _,
+                                                 0,
+                                                 empty_cstr(), 0),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  pub fn member_type(&self,
+                     decl: DIDecl<'ctx>,
+                     name: &str,
+                     size_bits: usize,
+                     align_bits: usize,
+                     offset_bits: usize,
+                     ty: Metadata<'ctx>) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateMemberType(self.l_builder,
+                                                  decl.scope.l_metadata,
+                                                  name.as_ptr() as _, name.len(),
+                                                  decl.file.l_metadata,
+                                                  decl.line as _,
+                                                  size_bits as _,
+                                                  align_bits as _,
+                                                  offset_bits as _,
+                                                  LLVMDIFlagZero,
+                                                  ty.l_metadata),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  pub fn enumerator(&self, name: &str, value: i64) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateEnumerator(self.l_builder,
+                                                  name.as_ptr() as _, name.len(),
+                                                  value,
+                                                  0),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  pub fn enumeration_type(&self,
+                          decl: DIDecl<'ctx>,
+                          name: &str,
+                          size_bits: usize,
+                          align_bits: usize,
+                          enumerators: &[Metadata<'ctx>],
+                          underlying: Metadata<'ctx>) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateEnumerationType(self.l_builder,
+                                                       decl.scope.l_metadata,
+                                                       name.as_ptr() as _, name.len(),
+                                                       decl.file.l_metadata,
+                                                       decl.line as _,
+                                                       size_bits as _,
+                                                       align_bits as _,
+                                                       enumerators.as_ptr() as _,
+                                                       enumerators.len() as _,
+                                                       underlying.l_metadata),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  /// Placeholder for a type that might refer to itself, see replace_temporary
+  pub fn temporary_type(&self, decl: DIDecl<'ctx>, name: &str) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        // DW_TAG_structure_type
+        l_metadata: LLVMDIBuilderCreateReplaceableCompositeType(self.l_builder,
+                                                                0x13,
+                                                                name.as_ptr() as _, name.len(),
+                                                                decl.scope.l_metadata,
+                                                                decl.file.l_metadata,
+                                                                decl.line as _,
+                                                                0, 0, 0,
+                                                                LLVMDIFlagFwdDecl,
+                                                                empty_cstr(), 0),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  /// Replace every use of a temporary type, then delete it
+  pub fn replace_temporary(&self, temporary: Metadata<'ctx>, replacement: Metadata<'ctx>) {
+    unsafe {
+      LLVMMetadataReplaceAllUsesWith(temporary.l_metadata, replacement.l_metadata)
+    }
+  }
+
+  pub fn auto_variable(&self,
+                       scope: Metadata<'ctx>,
+                       name: &str,
+                       file: Metadata<'ctx>,
+                       line: usize,
+                       ty: Metadata<'ctx>) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateAutoVariable(self.l_builder,
+                                                    scope.l_metadata,
+                                                    name.as_ptr() as _, name.len(),
+                                                    file.l_metadata,
+                                                    line as _,
+                                                    ty.l_metadata,
+                                                    1,
+                                                    LLVMDIFlagZero,
+                                                    0),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  /// Parameter variable, arg_no starts at 1
+  pub fn parameter_variable(&self,
+                            scope: Metadata<'ctx>,
+                            name: &str,
+                            arg_no: usize,
+                            file: Metadata<'ctx>,
+                            line: usize,
+                            ty: Metadata<'ctx>) -> Metadata<'ctx> {
+    unsafe {
+      Metadata {
+        l_metadata: LLVMDIBuilderCreateParameterVariable(self.l_builder,
+                                                         scope.l_metadata,
+                                                         name.as_ptr() as _, name.len(),
+                                                         arg_no as _,
+                                                         file.l_metadata,
+                                                         line as _,
+                                                         ty.l_metadata,
+                                                         1,
+                                                         LLVMDIFlagZero),
+        lifetime: PhantomData
+      }
+    }
+  }
+
+  /// Declare that a variable lives at storage, at the end of block
+  pub fn insert_declare(&self, storage: Value<'ctx>, variable: Metadata<'ctx>, location: Metadata<'ctx>, block: Block<'ctx>) {
+    assert!(storage.ty().is_ptr());
+
+    unsafe {
+      let l_expr = LLVMDIBuilderCreateExpression(self.l_builder, std::ptr::null_mut(), 0);
+      LLVMDIBuilderInsertDeclareAtEnd(self.l_builder,
+                                      storage.l_value,
+                                      variable.l_metadata,
+                                      l_expr,
+                                      location.l_metadata,
+                                      block.l_block);
+    }
+  }
+}
+
+/// Convert an option string to a C string
+fn c_string(s: &str) -> Result<CString, String> {
+  CString::new(s).map_err(|_| format!("{:?} contains a NUL byte", s))
+}
+
 /// Empty NUL-terminated C string
 fn empty_cstr() -> *mut c_char {
-  b"\0".as_ptr() as _
+  c"".as_ptr() as _
+}
+
+/// Copy the contents of a memory buffer, then free it
+unsafe fn take_buffer(buffer: LLVMMemoryBufferRef) -> Vec<u8> {
+  let data = std::slice::from_raw_parts(
+    LLVMGetBufferStart(buffer) as *const u8,
+    LLVMGetBufferSize(buffer)).to_vec();
+  LLVMDisposeMemoryBuffer(buffer);
+  data
+}
+
+extern "C" {
+  fn dup(fd: c_int) -> c_int;
+  fn dup2(old_fd: c_int, new_fd: c_int) -> c_int;
+  fn close(fd: c_int) -> c_int;
+}
+
+/// Call f, collecting everything written to stderr meanwhile
+unsafe fn capture_stderr<T>(f: impl FnOnce() -> T) -> (T, String) {
+  let (mut reader, writer) = match UnixStream::pair() {
+    Ok(pair) => pair,
+    Err(_) => return (f(), String::new())
+  };
+  let saved = dup(2);
+  dup2(writer.as_raw_fd(), 2);
+  let result = f();
+  dup2(saved, 2);
+  close(saved);
+  drop(writer);
+  let mut output = String::new();
+  let _ = reader.read_to_string(&mut output);
+  (result, output)
+}
+
+/// Turn LLVM's warnings about unknown CPUs and features into an error,
+/// passing any other output through
+fn unknown_subtarget(warnings: &str) -> Option<String> {
+  let mut error = None;
+  for line in warnings.lines() {
+    // e.g. 'foo' is not a recognized processor for this target (ignoring processor)
+    let message = match line.split('\'').collect::<Vec<_>>()[..] {
+      [ "", name, rest ] if rest.starts_with(" is not a recognized processor") => {
+        format!("Unknown target CPU {}", name)
+      }
+      [ "", name, rest ] if rest.starts_with(" is not a recognized feature") => {
+        format!("Unknown target feature {}", name)
+      }
+      _ => {
+        eprintln!("{}", line);
+        continue
+      }
+    };
+    error.get_or_insert(message);
+  }
+  error
 }
 
 /// Calculate the length of a C-style string (in bytes)
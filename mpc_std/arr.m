@@ -7,6 +7,6 @@
 
 import mem
 
-function length<T>(array: *T) -> Uintn {
+pub function length<T>(array: *T) -> Uintn {
   mem::size_of(array) / mem::size_of(&(*array)[0])
 }
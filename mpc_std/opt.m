@@ -7,20 +7,20 @@
 
 import prog
 
-enum Option<T>(
+pub enum Option<T>(
   Some (val: T),
   None
 )
 
-function some<T>(val: T) -> Option<T> {
+pub function some<T>(val: T) -> Option<T> {
   Option::Some(val)
 }
 
-function none<T>() -> Option<T> {
+pub function none<T>() -> Option<T> {
   Option::None
 }
 
-function unwrap<T>(o: Option<T>) -> T {
+pub function unwrap<T>(o: Option<T>) -> T {
   match o {
     s: Some => s.val,
     None    => prog::panic(c"Tried to unwrap None\n")
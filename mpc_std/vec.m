@@ -9,37 +9,37 @@ import mem
 import opt
 import prog
 
-struct Vec<T>(mem: *mut T, length: Uintn, capacity: Uintn)
+pub struct Vec<T>(mem: *mut T, length: Uintn, capacity: Uintn)
 
-function new<T>() -> Vec<T> {
+pub function new<T>() -> Vec<T> {
   Vec(nil, 0, 0)
 }
 
-function delete<T>(vec: *mut Vec<T>) {
+pub function delete<T>(vec: *mut Vec<T>) {
   mem::deallocate((*vec).mem)
 }
 
-function ensure_capacity<T>(vec: *mut Vec<T>, capacity: Uintn) {
+pub function ensure_capacity<T>(vec: *mut Vec<T>, capacity: Uintn) {
   if (*vec).capacity < capacity {
     (*vec).capacity = capacity * 2;
     (*vec).mem = mem::reallocate_contiguous((*vec).mem, capacity * 2);
   }
 }
 
-function push<T>(vec: *mut Vec<T>, val: T) {
+pub function push<T>(vec: *mut Vec<T>, val: T) {
   ensure_capacity(vec, (*vec).length + 1);
   *mem::ptr_off((*vec).mem, (*vec).length) = val;
   (*vec).length += 1;
 }
 
-function at<T>(vec: *mut Vec<T>, index: Uintn) -> *mut T {
+pub function at<T>(vec: *mut Vec<T>, index: Uintn) -> *mut T {
   if index >= (*vec).length {
     prog::panic(c"Tried to access vec out of bounds");
   }
   mem::ptr_off((*vec).mem, index)
 }
 
-function at_or_none<T>(vec: *mut Vec<T>, index: Uintn) -> opt::Option<*mut T> {
+pub function at_or_none<T>(vec: *mut Vec<T>, index: Uintn) -> opt::Option<*mut T> {
   if index < (*vec).length {
     opt::some(mem::ptr_off((*vec).mem, index))
   } else {
@@ -47,7 +47,7 @@ function at_or_none<T>(vec: *mut Vec<T>, index: Uintn) -> opt::Option<*mut T> {
   }
 }
 
-function pop<T>(vec: *mut Vec<T>) -> T {
+pub function pop<T>(vec: *mut Vec<T>) -> T {
   if (*vec).length == 0 {
     prog::panic(c"Tried to pop form empty vec");
   }
@@ -55,7 +55,7 @@ function pop<T>(vec: *mut Vec<T>) -> T {
   *mem::ptr_off((*vec).mem, (*vec).length)
 }
 
-function pop_or_none<T>(vec: *mut Vec<T>) -> opt::Option<T> {
+pub function pop_or_none<T>(vec: *mut Vec<T>) -> opt::Option<T> {
   if (*vec).length > 0 {
     (*vec).length -= 1;
     opt::some(*mem::ptr_off((*vec).mem, (*vec).length))
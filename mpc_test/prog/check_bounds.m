@@ -0,0 +1,13 @@
+/*
+CHECKS bounds
+EXIT 134
+STDERR
+mpc_test/prog/check_bounds.m:12:3: index out of bounds
+END
+*/
+
+function main() -> Int32 {
+  let arr: [4]Int32 = [ 0, 1, 2, 3 ];
+  let index: Uintn = 4;
+  arr[index]
+}
@@ -0,0 +1,13 @@
+/*
+CHECKS div-zero,overflow
+EXIT 134
+STDERR
+mpc_test/prog/check_div.m:12:3: division overflowed
+END
+*/
+
+function main() -> Int32 {
+  let x: Int32 = -2147483647 - 1;
+  let y: Int32 = 1 - 2;
+  x / y
+}
@@ -0,0 +1,12 @@
+/*
+CHECKS null
+EXIT 134
+STDERR
+mpc_test/prog/check_null.m:11:3: nil pointer dereference
+END
+*/
+
+function main() -> Int32 {
+  let p: *Int32 = nil;
+  *p
+}
@@ -0,0 +1,12 @@
+/*
+CHECKS overflow
+EXIT 134
+STDERR
+mpc_test/prog/check_overflow.m:11:3: addition overflowed
+END
+*/
+
+function main() -> Int32 {
+  let x: Int32 = 2147483647;
+  x + 1
+}
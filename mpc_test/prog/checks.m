@@ -0,0 +1,18 @@
+/*
+CHECKS bounds,overflow,null,div-zero
+STDOUT
+3 -2147483647 7 2
+END
+*/
+
+import libc
+
+function main() -> Int32 {
+  let arr: [4]Int32 = [ 0, 1, 2, 3 ];
+  let index: Uintn = 3;
+  let x: Int32 = 2147483647;
+  let y = 7;
+  let p = &y;
+  libc::printf(c"%d %d %d %d\n", arr[index], -x, *p, x / 1000000000);
+  0
+}
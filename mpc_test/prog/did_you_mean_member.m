@@ -0,0 +1,61 @@
+/*
+ERRORS
+error[E0206]: Match case for unknown variant Sqare
+  --> mpc_test/prog/did_you_mean_member.m:40:3
+   |
+40 |   match shape {
+   |   ^^^^^^^^^^^^^
+   = help: did you mean `Square`?
+error[E0204]: Type Point has no field named rigth
+  --> mpc_test/prog/did_you_mean_member.m:47:3
+   |
+47 |   p.rigth
+   |   ^^^^^^^
+   = help: did you mean `right`?
+error[E0204]: Type Num has no field named flot
+  --> mpc_test/prog/did_you_mean_member.m:51:3
+   |
+51 |   n.flot
+   |   ^^^^^^
+   = help: did you mean `flt`?
+error[E0204]: Type (first: '1, second: '2) has no field named secnd
+  --> mpc_test/prog/did_you_mean_member.m:56:3
+   |
+56 |   tup.secnd
+   |   ^^^^^^^^^
+   = help: did you mean `second`?
+END
+*/
+
+struct Point(left: Int32, right: Int32)
+
+union Num(int: Int64, flt: Double)
+
+enum Shape(
+  Circle(radius: Int32),
+  Square(side: Int32)
+)
+
+function enum_variant(shape: Shape) -> Int32 {
+  match shape {
+    c: Circle => 3 * c.radius * c.radius,
+    s: Sqare => s.side * s.side
+  }
+}
+
+function struct_field(p: Point) -> Int32 {
+  p.rigth
+}
+
+function union_field(n: Num) -> Double {
+  n.flot
+}
+
+function tuple_field() -> Int32 {
+  let tup = (first: 1, second: 2);
+  tup.secnd
+}
+
+function main() -> Int32 {
+  enum_variant(Shape::Square(side: 1)) + struct_field(Point(left: 1, right: 2)) + tuple_field()
+}
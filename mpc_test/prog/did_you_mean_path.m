@@ -0,0 +1,61 @@
+/*
+ERRORS
+error[E0101]: Unresolved path cuont
+  --> mpc_test/prog/did_you_mean_path.m:38:3
+   |
+38 |   cuont
+   |   ^^^^^
+   = help: did you mean `count`?
+error[E0101]: Unresolved path aera
+  --> mpc_test/prog/did_you_mean_path.m:42:3
+   |
+42 |   aera(2)
+   |   ^^^^
+   = help: did you mean `area`?
+error[E0101]: Unresolved path Shape::Cirle
+  --> mpc_test/prog/did_you_mean_path.m:51:3
+   |
+51 |   Shape::Cirle(radius: 1)
+   |   ^^^^^^^^^^^^
+   = help: did you mean `Circle`?
+error[E0101]: Unresolved path lbic::puts
+  --> mpc_test/prog/did_you_mean_path.m:55:3
+   |
+55 |   lbic::puts(c"hello");
+   |   ^^^^^^^^^^
+   = help: did you mean `libc`?
+END
+*/
+
+import libc
+
+function area(side: Int32) -> Int32 {
+  side * side
+}
+
+function scope_stack() -> Int32 {
+  let count = 1;
+  cuont
+}
+
+function module_symbol() -> Int32 {
+  aera(2)
+}
+
+enum Shape(
+  Circle(radius: Int32),
+  Square(side: Int32)
+)
+
+function enum_variant() -> Shape {
+  Shape::Cirle(radius: 1)
+}
+
+function imported_module() {
+  lbic::puts(c"hello");
+}
+
+function main() -> Int32 {
+  libc::puts(c"unreachable");
+  0
+}
@@ -0,0 +1,16 @@
+/*
+ERRORS
+error[E0008]: Unexpected token
+  --> mpc_test/prog/import_empty.m:14:1
+   |
+14 | function main() -> Int32 {
+   | ^^^^^^^^
+   = note: expected one of Identifier
+END
+*/
+
+from opt import
+
+function main() -> Int32 {
+  0
+}
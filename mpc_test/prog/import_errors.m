@@ -0,0 +1,26 @@
+/*
+ERRORS
+error[E0011]: Re-definition of puts
+  --> mpc_test/prog/import_errors.m:22:10
+   |
+20 | from libc import puts
+   |                  ---- previous definition of puts here
+...
+22 | function puts() {}
+   |          ^^^^
+error[E0105]: Unresolved import nothing
+  --> mpc_test/prog/import_errors.m:19:17
+   |
+19 | from opt import nothing
+   |                 ^^^^^^^
+END
+*/
+
+from opt import nothing
+from libc import puts
+
+function puts() {}
+
+function main() -> Int32 {
+  0
+}
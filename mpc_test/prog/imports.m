@@ -0,0 +1,16 @@
+/*
+STDOUT
+1 2
+END
+*/
+
+import vec as v
+from libc import printf
+from opt import some, unwrap as get
+
+function main() -> Int32 {
+  let mut ints = v::new();
+  v::push(&ints, 1);
+  printf(c"%d %d\n", *v::at(&ints, 0), get(some(2)));
+  0
+}
@@ -0,0 +1,10 @@
+/*
+ERRORS-JSON
+{"severity":"error","code":"E0008","message":"Unexpected token","file":"mpc_test/prog/json_syntax.m","span":{"start":{"line":8,"column":13},"end":{"line":8,"column":14}},"labels":[{"primary":true,"file":"mpc_test/prog/json_syntax.m","span":{"start":{"line":8,"column":13},"end":{"line":8,"column":14}},"message":""}],"children":[{"severity":"note","message":"expected one of \")\""}]}
+END
+*/
+
+function main() -> Int32 {
+  let x = (1;
+  0
+}
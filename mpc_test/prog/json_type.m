@@ -0,0 +1,12 @@
+/*
+ERRORS-JSON
+{"severity":"error","code":"E0201","message":"Cannot unify types Int32 and Bool","file":"mpc_test/prog/json_type.m","span":{"start":{"line":9,"column":18},"end":{"line":9,"column":22}},"labels":[{"primary":true,"file":"mpc_test/prog/json_type.m","span":{"start":{"line":9,"column":18},"end":{"line":9,"column":22}},"message":""},{"primary":false,"file":"mpc_test/prog/json_type.m","span":{"start":{"line":9,"column":10},"end":{"line":9,"column":15}},"message":"expected because of this annotation"}],"children":[]}
+{"severity":"error","code":"E0201","message":"Cannot unify types Bool and Int32","file":"mpc_test/prog/json_type.m","span":{"start":{"line":10,"column":18},"end":{"line":10,"column":19}},"labels":[{"primary":true,"file":"mpc_test/prog/json_type.m","span":{"start":{"line":10,"column":18},"end":{"line":10,"column":19}},"message":""},{"primary":false,"file":"mpc_test/prog/json_type.m","span":{"start":{"line":10,"column":11},"end":{"line":10,"column":15}},"message":"expected because of this annotation"}],"children":[]}
+END
+*/
+
+function main() -> Int32 {
+  let x: Int32 = true;
+  let _y: Bool = x;
+  0
+}
@@ -0,0 +1,31 @@
+/*
+STDOUT
+42 2
+END
+SYMBOLS
+T maple_add
+t add
+D counter
+d total
+R limit
+r base
+END
+*/
+
+import libc
+
+export function maple_add(a: Int32, b: Int32) -> Int32 { add(a, b) }
+
+function add(a: Int32, b: Int32) -> Int32 { a + b }
+
+export data mut counter: Int32 = 1
+data mut total: Int32 = 1
+export data limit: Int32 = 40
+data base: Int32 = 2
+
+function main() -> Int32 {
+  counter = counter + 1;
+  total = maple_add(limit, base);
+  libc::printf(c"%d %d\n", total, counter);
+  0
+}
@@ -0,0 +1,16 @@
+/*
+ERRORS
+error[E0107]: Generic function first cannot be exported
+  --> mpc_test/prog/linkage_generic.m:12:17
+   |
+12 | export function first<T>(a: T, _b: T) -> T { a }
+   |                 ^^^^^
+   = help: export a non-generic function calling the instance instead
+END
+*/
+
+export function first<T>(a: T, _b: T) -> T { a }
+
+function main() -> Int32 {
+  first(0, 1)
+}
@@ -0,0 +1,53 @@
+/*
+DENY unused-variables,unused-imports,unreachable-code,shadowed-locals
+ERRORS
+error[unused-variables]: Unused variable b
+  --> mpc_test/prog/lint_deny.m:43:24
+   |
+43 | function add(a: Int32, b: Int32) -> Int32 {
+   |                        ^
+   = note: lint `unused-variables` is denied
+error[unreachable-code]: Unreachable code
+  --> mpc_test/prog/lint_deny.m:52:3
+   |
+51 |   return x;
+   |   -------- any code following this expression is unreachable
+52 |   0
+   |   ^
+   = note: lint `unreachable-code` is denied
+error[shadowed-locals]: Local x shadows an earlier definition
+  --> mpc_test/prog/lint_deny.m:49:7
+   |
+48 |   let x = 1;
+   |       - previously defined here
+49 |   let x = add(x, 2);
+   |       ^
+   = note: lint `shadowed-locals` is denied
+error[unused-variables]: Unused variable unused
+  --> mpc_test/prog/lint_deny.m:50:7
+   |
+50 |   let unused = 3;
+   |       ^^^^^^
+   = note: lint `unused-variables` is denied
+error[unused-imports]: Unused import libc
+  --> mpc_test/prog/lint_deny.m:41:8
+   |
+41 | import libc
+   |        ^^^^
+   = note: lint `unused-imports` is denied
+END
+*/
+
+import libc
+
+function add(a: Int32, b: Int32) -> Int32 {
+  a
+}
+
+function main() -> Int32 {
+  let x = 1;
+  let x = add(x, 2);
+  let unused = 3;
+  return x;
+  0
+}
@@ -0,0 +1,23 @@
+/*
+DENY warnings
+ERRORS-JSON
+{"severity":"error","code":"unused-variables","message":"Unused variable b","file":"mpc_test/prog/lint_deny_warnings.m","span":{"start":{"line":13,"column":24},"end":{"line":13,"column":25}},"labels":[{"primary":true,"file":"mpc_test/prog/lint_deny_warnings.m","span":{"start":{"line":13,"column":24},"end":{"line":13,"column":25}},"message":""}],"children":[{"severity":"note","message":"lint `unused-variables` is denied"}]}
+{"severity":"error","code":"unreachable-code","message":"Unreachable code","file":"mpc_test/prog/lint_deny_warnings.m","span":{"start":{"line":22,"column":3},"end":{"line":22,"column":4}},"labels":[{"primary":true,"file":"mpc_test/prog/lint_deny_warnings.m","span":{"start":{"line":22,"column":3},"end":{"line":22,"column":4}},"message":""},{"primary":false,"file":"mpc_test/prog/lint_deny_warnings.m","span":{"start":{"line":21,"column":3},"end":{"line":21,"column":11}},"message":"any code following this expression is unreachable"}],"children":[{"severity":"note","message":"lint `unreachable-code` is denied"}]}
+{"severity":"error","code":"unused-variables","message":"Unused variable unused","file":"mpc_test/prog/lint_deny_warnings.m","span":{"start":{"line":20,"column":7},"end":{"line":20,"column":13}},"labels":[{"primary":true,"file":"mpc_test/prog/lint_deny_warnings.m","span":{"start":{"line":20,"column":7},"end":{"line":20,"column":13}},"message":""}],"children":[{"severity":"note","message":"lint `unused-variables` is denied"}]}
+{"severity":"error","code":"unused-imports","message":"Unused import libc","file":"mpc_test/prog/lint_deny_warnings.m","span":{"start":{"line":11,"column":8},"end":{"line":11,"column":12}},"labels":[{"primary":true,"file":"mpc_test/prog/lint_deny_warnings.m","span":{"start":{"line":11,"column":8},"end":{"line":11,"column":12}},"message":""}],"children":[{"severity":"note","message":"lint `unused-imports` is denied"}]}
+END
+*/
+
+import libc
+
+function add(a: Int32, b: Int32) -> Int32 {
+  a
+}
+
+function main() -> Int32 {
+  let x = 1;
+  let x = add(x, 2);
+  let unused = 3;
+  return x;
+  0
+}
@@ -0,0 +1,31 @@
+/*
+STDOUT
+3 4 5
+END
+SYMBOLS
+t vec::push<Int32>
+t vec::push<opt::Option<*Int8>>
+t opt::some<*Int8>
+t pick<[2]Int64, (a: Int32, b: Bool)>
+t helper
+END
+*/
+
+import libc
+import opt
+import vec
+
+function pick<A, B>(a: A, _b: B) -> A { a }
+
+function helper() -> Int32 { 3 }
+
+function main() -> Int32 {
+  let mut ints = vec::new();
+  vec::push(&ints, helper());
+  let mut strs = vec::new();
+  vec::push(&strs, opt::some(c"4"));
+  let arr: [2]Int64 = [ 5, 6 ];
+  let arr = pick(arr, (a: 1, b: true));
+  libc::printf(c"%d %s %ld\n", *vec::at(&ints, 0), opt::unwrap(*vec::at(&strs, 0)), arr[0]);
+  0
+}
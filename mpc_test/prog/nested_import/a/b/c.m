@@ -0,0 +1 @@
+pub function value() -> Int32 { 3 }
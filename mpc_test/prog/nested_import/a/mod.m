@@ -0,0 +1,3 @@
+import b
+
+pub function value() -> Int32 { b::value() - 1 }
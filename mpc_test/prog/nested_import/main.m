@@ -0,0 +1,15 @@
+/*
+STDOUT
+3 1 2
+END
+*/
+
+// a::b::c is a/b/c.m, next to a/b/mod.m, while a itself is a/mod.m
+import libc
+import a::b::c
+import a
+
+function main() -> Int32 {
+  libc::printf(c"%d %d %d\n", a::b::c::value(), a::value(), a::b::value());
+  0
+}
@@ -0,0 +1,27 @@
+/*
+ERRORS
+error[E0008]: Unexpected token
+  --> mpc_test/prog/recover_syntax.m:19:13
+   |
+19 |   let x = (1;
+   |             ^
+   = note: expected one of ")"
+error[E0008]: Unexpected token
+  --> mpc_test/prog/recover_syntax.m:23:24
+   |
+23 | struct Broken(a: Int32 b: Int32)
+   |                        ^
+   = note: expected one of ")", ",", "=", ">", "const", "data", "enum", "export", "extern", "from", "function", "import", "pub", "struct", "type", "union", "{", "}"
+END
+*/
+
+function first() -> Int32 {
+  let x = (1;
+  x
+}
+
+struct Broken(a: Int32 b: Int32)
+
+function main() -> Int32 {
+  0
+}
@@ -0,0 +1,37 @@
+/*
+ERRORS
+error[E0201]: Cannot unify types Int32 and Bool
+  --> mpc_test/prog/recover_types.m:26:18
+   |
+26 |   let x: Int32 = true;
+   |          -----   ^^^^
+   |          expected because of this annotation
+error[E0205]: Cannot bound type Bool by Int
+  --> mpc_test/prog/recover_types.m:31:17
+   |
+31 |   let y: Bool = 1;
+   |          ----   ^
+   |          expected because of this annotation
+   = note: Bool is not an integer type
+error[E0205]: Cannot bound type Bool by Num
+  --> mpc_test/prog/recover_types.m:32:3
+   |
+32 |   y + 1
+   |   ^^^^^
+   = note: Bool is not a numeric type
+END
+*/
+
+function first() -> Int32 {
+  let x: Int32 = true;
+  x
+}
+
+function second() -> Bool {
+  let y: Bool = 1;
+  y + 1
+}
+
+function main() -> Int32 {
+  first()
+}
@@ -0,0 +1,16 @@
+/*
+SANITIZE address
+EXIT 1
+STDERR-CONTAINS
+ERROR: AddressSanitizer: heap-buffer-overflow
+WRITE of size 4
+END
+*/
+
+import mem
+
+function main() -> Int32 {
+  let ints: *mut Int32 = mem::allocate_contiguous(2);
+  *mem::ptr_off(ints, 2) = 1;
+  0
+}
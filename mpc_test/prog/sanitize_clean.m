@@ -0,0 +1,17 @@
+/*
+SANITIZE address,undefined
+STDOUT
+3
+END
+*/
+
+import libc
+import mem
+
+function main() -> Int32 {
+  let ints: *mut Int32 = mem::allocate_contiguous(2);
+  *mem::ptr_off(ints, 1) = 3;
+  libc::printf(c"%d\n", *mem::ptr_off(ints, 1));
+  mem::deallocate(ints);
+  0
+}
@@ -0,0 +1,12 @@
+/*
+SANITIZE undefined
+EXIT 134
+STDERR
+mpc_test/prog/sanitize_undefined.m:11:3: runtime error: addition overflowed
+END
+*/
+
+function main() -> Int32 {
+  let x: Int32 = 2147483647;
+  x + 1
+}
@@ -0,0 +1,7 @@
+import libc
+import helper
+
+pub function hello() {
+  libc::puts(c"extra");
+  helper::hello();
+}
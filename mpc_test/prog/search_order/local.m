@@ -0,0 +1,5 @@
+import libc
+
+pub function hello() {
+  libc::puts(c"local");
+}
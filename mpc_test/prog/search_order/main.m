@@ -0,0 +1,20 @@
+/*
+INCLUDE inc
+STDOUT
+local
+extra
+helper
+END
+*/
+
+// local.m is found next to this file before the include directory,
+// extra.m only in the include directory, and helper.m, imported by
+// extra.m, in the directory of the module importing it
+import local
+import extra
+
+function main() -> Int32 {
+  local::hello();
+  extra::hello();
+  0
+}
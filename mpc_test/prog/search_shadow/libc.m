@@ -0,0 +1 @@
+pub function puts(_s: *Int8) -> Int32 { 0 }
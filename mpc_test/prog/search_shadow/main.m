@@ -0,0 +1,17 @@
+/*
+ERRORS
+error[E0101]: Unresolved path libc::printf
+  --> mpc_test/prog/search_shadow/main.m:15:3
+   |
+15 |   libc::printf(c"unreachable\n");
+   |   ^^^^^^^^^^^^
+END
+*/
+
+// The directory of the importing file is searched before the standard library
+import libc
+
+function main() -> Int32 {
+  libc::printf(c"unreachable\n");
+  0
+}
@@ -0,0 +1,19 @@
+/*
+ERRORS
+error[E0209]: Incorrect number of type parameters
+  --> mpc_test/prog/type_arg_count.m:17:10
+   |
+14 | struct Box<T>(val: T)
+   |        --- defined here
+...
+17 |   let b: Box<Int32, Bool> = Box(val: 1);
+   |          ^^^^^^^^^^^^^^^^
+END
+*/
+
+struct Box<T>(val: T)
+
+function main() -> Int32 {
+  let b: Box<Int32, Bool> = Box(val: 1);
+  b.val
+}
@@ -0,0 +1,13 @@
+/*
+STDOUT
+area 9
+END
+*/
+
+import shapes
+
+function main() -> Int32 {
+  let square = shapes::Square(side: 3);
+  shapes::libc::printf(c"area %d\n", shapes::area(square.side));
+  0
+}
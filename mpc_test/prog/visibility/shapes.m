@@ -0,0 +1,4 @@
+pub import libc
+pub from inner import area
+
+pub struct Square(side: Int32)
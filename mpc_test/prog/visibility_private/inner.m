@@ -0,0 +1,3 @@
+pub function area(side: Int32) -> Int32 {
+  side * side
+}
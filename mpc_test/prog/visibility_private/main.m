@@ -0,0 +1,31 @@
+/*
+ERRORS
+error[E0106]: secret is private
+  --> mpc_test/prog/visibility_private/main.m:27:20
+   |
+27 | from shapes import secret
+   |                    ^^^^^^
+  ::: mpc_test/prog/visibility_private/shapes.m:3:10
+   |
+ 3 | function secret() -> Int32 {
+   |          ------ secret is defined here
+   = help: mark secret as `pub` to use it from other modules
+error[E0106]: inner is private
+  --> mpc_test/prog/visibility_private/main.m:30:3
+   |
+30 |   shapes::inner::area(3) + shapes::secret()
+   |   ^^^^^^^^^^^^^^^^^^^
+  ::: mpc_test/prog/visibility_private/shapes.m:1:8
+   |
+ 1 | import inner
+   |        ----- inner is defined here
+   = help: mark inner as `pub` to use it from other modules
+END
+*/
+
+import shapes
+from shapes import secret
+
+function main() -> Int32 {
+  shapes::inner::area(3) + shapes::secret()
+}
@@ -0,0 +1,5 @@
+import inner
+
+function secret() -> Int32 {
+  inner::area(2)
+}
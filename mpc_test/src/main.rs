@@ -4,12 +4,12 @@
  */
 
 use mpc::util::MRes;
-use std::ffi::OsStr;
 use std::{fmt, fs};
 use std::error::Error;
 use std::fmt::{Debug, Formatter};
+use std::os::unix::process::ExitStatusExt;
 use std::path::Path;
-use std::process::{Command};
+use std::process::{Command, ExitStatus};
 use regex::Regex;
 
 /// Directory of test programs
@@ -20,19 +20,21 @@ const OUTPUT_DIR: &str = "mpc_test/output";
 
 fn main() {
   for cur in fs::read_dir(PROG_DIR).unwrap() {
-    let src_path = cur.unwrap().path();
-    let file_name = src_path.file_name().unwrap();
-    let obj_path = Path::new(OUTPUT_DIR)
-      .join(&file_name)
-      .with_extension("o");
+    let path = cur.unwrap().path();
+    // Programs made of several modules are directories, with the root module in main.m
+    let src_path = if path.is_dir() {
+      path.join("main.m")
+    } else if path.extension().is_some_and(|ext| ext == "m") {
+      path.clone()
+    } else {
+      continue
+    };
+    let file_name = path.file_name().unwrap();
     let bin_path = Path::new(OUTPUT_DIR)
-      .join(&file_name)
+      .join(file_name)
       .with_extension("");
 
-    match mpc::compile(&src_path, &obj_path, mpc::CompileTo::Object, None)
-      .and_then(|_| link(&obj_path, &bin_path))
-      .and_then(|_| run_and_check(&src_path, &bin_path))
-    {
+    match compile_and_check(&src_path, &bin_path) {
       Ok(_) => println!("[OK] {}", file_name.to_str().unwrap()),
       Err(err) => println!("[ERR] {} {}", file_name.to_str().unwrap(), err),
     }
@@ -43,50 +45,140 @@ fn main() {
 /// Error conditions
 #[derive(Debug)]
 enum TestError {
-  LinkFailure,
   ExitFailure,
-  IncorrectOutput
+  IncorrectOutput,
+  MissingErrors,
+  IncorrectErrors(String),
+  MissingSymbol(String)
 }
 
 impl fmt::Display for TestError {
   fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
     match self {
-      TestError::LinkFailure => write!(f, "Failed to link"),
       TestError::ExitFailure => write!(f, "Test exited with error"),
       TestError::IncorrectOutput => write!(f, "Incorrect test output"),
+      TestError::MissingErrors => write!(f, "Test compiled, but errors were expected"),
+      TestError::IncorrectErrors(errors) => write!(f, "Incorrect errors:\n{}", errors),
+      TestError::MissingSymbol(symbol) => write!(f, "Missing symbol {}", symbol),
     }
   }
 }
 
 impl Error for TestError {}
 
-/// Link an object file into an executable
-fn link(obj_path: &Path, bin_path: &Path) -> MRes<()> {
-  let status = Command::new("cc")
-    .args([OsStr::new("-o"), bin_path.as_os_str(), obj_path.as_os_str()])
-    .status()?;
+/// Contents of a section spanning several lines, ended by END
+fn section<'a>(source: &'a str, name: &str) -> Option<&'a str> {
+  Regex::new(&format!(r"(?ms)^{}\n(.*?)^END$", name)).unwrap()
+    .captures(source)
+    .and_then(|x| x.get(1))
+    .map(|x| x.as_str())
+}
+
+/// Contents of a single line section
+fn section_line<'a>(source: &'a str, name: &str) -> Option<&'a str> {
+  Regex::new(&format!(r"(?m)^{} +(.*?) *$", name)).unwrap()
+    .captures(source)
+    .and_then(|x| x.get(1))
+    .map(|x| x.as_str())
+}
+
+/// Compile a program, then run and check it, or check the errors it was expected to fail with
+fn compile_and_check(src_path: &Path, bin_path: &Path) -> MRes<()> {
+  let source = fs::read_to_string(src_path).unwrap();
 
-  match status.success() {
-    true => Ok(()),
-    false => Err(Box::new(TestError::LinkFailure))
+  let mut options = mpc::CompileOptions::default();
+  if let Some(checks) = section_line(&source, "CHECKS") {
+    options.checks = checks.split(',')
+      .map(|check| check.parse())
+      .collect::<Result<_, _>>()?;
+  }
+  if let Some(sanitizers) = section_line(&source, "SANITIZE") {
+    options.sanitizers = sanitizers.split(',')
+      .map(|sanitizer| sanitizer.parse())
+      .collect::<Result<_, _>>()?;
+  }
+  // Lints to deny, warnings denies all of them like --deny-warnings
+  if let Some(lints) = section_line(&source, "DENY") {
+    for lint in lints.split(',') {
+      match lint {
+        "warnings" => options.lints.deny_warnings = true,
+        _ => { options.lints.levels.insert(lint.parse()?, mpc::LintLevel::Deny); }
+      }
+    }
+  }
+  // Include directories are relative to the program
+  if let Some(dirs) = section_line(&source, "INCLUDE") {
+    let base = src_path.parent().unwrap();
+    options.include_dirs = dirs.split_whitespace()
+      .map(|dir| base.join(dir))
+      .collect();
+  }
+
+  let compile_to = mpc::CompileTo::Executable(mpc::LinkOptions::default());
+  let result = mpc::compile(src_path, bin_path, compile_to, &options);
+
+  // Check expected errors
+  let expected_errors = section(&source, "ERRORS");
+  let expected_json_errors = section(&source, "ERRORS-JSON");
+  if expected_errors.is_none() && expected_json_errors.is_none() {
+    return result
+      .map_err(|diagnostics| diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .into())
+      .and_then(|_| run_and_check(&source, bin_path))
+  }
+  let diagnostics = match result {
+    Ok(_) => Err(Box::new(TestError::MissingErrors))?,
+    Err(diagnostics) => diagnostics
+  };
+  if let Some(expected) = expected_errors {
+    let errors: String = diagnostics
+      .iter()
+      .map(|diagnostic| format!("{}\n", diagnostic))
+      .collect();
+    if errors != expected {
+      Err(Box::new(TestError::IncorrectErrors(errors)))?
+    }
   }
+  if let Some(expected) = expected_json_errors {
+    let mut errors = Vec::new();
+    for diagnostic in diagnostics.iter() {
+      diagnostic.render_json(&mut errors)?;
+    }
+    let errors = String::from_utf8(errors)?;
+    if errors != expected {
+      Err(Box::new(TestError::IncorrectErrors(errors)))?
+    }
+  }
+
+  Ok(())
+}
+
+/// Exit code of a process, or 128 plus the signal that killed it like in the shell
+fn exit_code(status: ExitStatus) -> i32 {
+  status.code().unwrap_or_else(|| 128 + status.signal().unwrap())
 }
 
 /// Run and check program output
-fn run_and_check(src_path: &Path, bin_path: &Path) -> MRes<()> {
+fn run_and_check(source: &str, bin_path: &Path) -> MRes<()> {
   // Parse source
-  let source = fs::read_to_string(src_path).unwrap();
-  let args = Regex::new(r"ARGS *(.*) *\n")?
-    .captures(&source)
-    .and_then(|x| x.get(1))
-    .and_then(|x| {
-      Some(Regex::new(r" +").unwrap()
-        .split(x.as_str())
-        .collect())
+  let args: Vec<&str> = section_line(source, "ARGS")
+    .map(|x| {
+      Regex::new(r" +").unwrap()
+        .split(x)
+        .collect()
     })
-    .unwrap_or_else(Vec::new);
-  let expected_stdout = Regex::new(r"(?s)STDOUT\n(.*)END", )?.captures(&source).and_then(|x| x.get(1));
-  let expected_stderr = Regex::new(r"(?s)STDERR\n(.*)END", )?.captures(&source).and_then(|x| x.get(1));
+    .unwrap_or_default();
+  let expected_stdout = section(source, "STDOUT");
+  let expected_stderr = section(source, "STDERR");
+  let expected_stderr_lines = section(source, "STDERR-CONTAINS");
+  let expected_exit = match section_line(source, "EXIT") {
+    Some(code) => code.parse()?,
+    None => 0
+  };
 
   // Execute program
   let output = Command::new(bin_path)
@@ -94,23 +186,49 @@ fn run_and_check(src_path: &Path, bin_path: &Path) -> MRes<()> {
     .output()?;
 
   // Check status
-  if !output.status.success() {
+  if exit_code(output.status) != expected_exit {
     Err(Box::new(TestError::ExitFailure))?
   }
 
   // Check expected output
   match expected_stdout {
-    Some(s) if output.stdout != s.as_str().as_bytes() => {
+    Some(s) if output.stdout != s.as_bytes() => {
       Err(Box::new(TestError::IncorrectOutput))?
     }
     _ => ()
   }
   match expected_stderr {
-    Some(s) if output.stderr != s.as_str().as_bytes() => {
+    Some(s) if output.stderr != s.as_bytes() => {
       Err(Box::new(TestError::IncorrectOutput))?
     }
     _ => ()
   }
+  // Output that differs between runs, e.g. addresses, is checked line by line
+  if let Some(lines) = expected_stderr_lines {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !lines.lines().all(|line| stderr.contains(line)) {
+      Err(Box::new(TestError::IncorrectOutput))?
+    }
+  }
+
+  // Check expected symbols, each listed with its nm type and demangled name
+  if let Some(symbols) = section(source, "SYMBOLS") {
+    let output = Command::new("nm")
+      .arg(bin_path)
+      .output()?;
+    let table = mpc::demangle_text(&String::from_utf8_lossy(&output.stdout));
+    let entry = Regex::new(r"^\S*\s+(\S .*)$").unwrap();
+    let table: Vec<&str> = table
+      .lines()
+      .filter_map(|line| entry.captures(line).and_then(|x| x.get(1)))
+      .map(|x| x.as_str())
+      .collect();
+    for symbol in symbols.lines() {
+      if !table.contains(&symbol) {
+        Err(Box::new(TestError::MissingSymbol(symbol.to_string())))?
+      }
+    }
+  }
 
   Ok(())
 }